@@ -0,0 +1,56 @@
+// Throughput of a file body sent to a loopback connection, copied through a buffer or with sendfile.
+// cargo bench --bench sendfile
+
+use std::io::{self, Read};
+use std::net::{TcpListener, TcpStream};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use arkos::core::body::BodyFile;
+
+const FILE_SIZE: usize = 64 * 1024 * 1024;
+const ROUNDS: usize = 16;
+
+fn main() -> io::Result<()> {
+    let path = std::env::temp_dir().join(format!("arkos-bench-{}", std::process::id()));
+    std::fs::write(&path, vec![b'a'; FILE_SIZE])?;
+    let body = BodyFile::open(&path)?;
+    std::fs::remove_file(&path)?;
+
+    let buffered = measure(|stream| body.copy_to(stream))?;
+    let sendfile = measure(|stream| body.send_to(stream))?;
+    println!("buffered copy: {:>8.1} MiB/s", throughput(buffered));
+    println!("send_to:       {:>8.1} MiB/s", throughput(sendfile));
+    Ok(())
+}
+
+// Time to send the body ROUNDS times to a client draining the connection.
+fn measure<F: Fn(&mut TcpStream) -> io::Result<()>>(send: F) -> io::Result<Duration> {
+    let listener = TcpListener::bind("127.0.0.1:0")?;
+    let address = listener.local_addr()?;
+    let client = thread::spawn(move || -> io::Result<usize> {
+        let mut stream = TcpStream::connect(address)?;
+        let mut buf = vec![0u8; 256 * 1024];
+        let mut received = 0;
+        loop {
+            match stream.read(&mut buf)? {
+                0 => return Ok(received),
+                n => received += n,
+            }
+        }
+    });
+    let (mut stream, _) = listener.accept()?;
+    let start = Instant::now();
+    for _ in 0..ROUNDS {
+        send(&mut stream)?;
+    }
+    drop(stream);
+    let received = client.join().expect("the client has panicked")?;
+    let elapsed = start.elapsed();
+    assert_eq!(FILE_SIZE * ROUNDS, received);
+    Ok(elapsed)
+}
+
+fn throughput(elapsed: Duration) -> f64 {
+    (FILE_SIZE * ROUNDS) as f64 / (1024.0 * 1024.0) / elapsed.as_secs_f64()
+}
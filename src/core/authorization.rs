@@ -0,0 +1,109 @@
+use std::fmt;
+
+use crate::wrapper::base64;
+
+
+/// Credentials sent in the Authorization header of a Request. (RFC 9110 - 11.6.2)
+#[derive(Clone, PartialEq, Eq)]
+pub enum Authorization {
+    /// `Basic` scheme, with the decoded user and password. (RFC 7617)
+    Basic { user: String, password: String },
+    /// `Bearer` scheme, with the token. (RFC 6750)
+    Bearer(String),
+    /// Any other scheme, with its credentials as sent.
+    Other { scheme: String, credentials: String },
+}
+
+impl Authorization {
+
+    /// Will parse the value of an Authorization header. The scheme is case-insensitive.
+    /// Returns None if the header is malformed, as Basic credentials that are not base64-encoded `user:password`.
+    pub fn parse(value: &str) -> Option<Authorization> {
+        let value = value.trim();
+        let (scheme, credentials) = value.split_once(' ').map(|(s, c)| (s, c.trim())).unwrap_or((value, ""));
+        if scheme.is_empty() {
+            return None;
+        }
+
+        if scheme.eq_ignore_ascii_case("Basic") {
+            let decoded = String::from_utf8(base64::decode(credentials)?).ok()?;
+            let (user, password) = decoded.split_once(':')?;
+            Some(Authorization::Basic { user: user.into(), password: password.into() })
+        } else if scheme.eq_ignore_ascii_case("Bearer") {
+            match credentials.is_empty() || credentials.contains(' ') {
+                true => None,
+                false => Some(Authorization::Bearer(credentials.into())),
+            }
+        } else {
+            Some(Authorization::Other { scheme: scheme.into(), credentials: credentials.into() })
+        }
+    }
+
+    /// Name of the scheme: `Basic`, `Bearer` or the one sent.
+    pub fn scheme(&self) -> &str {
+        match self {
+            Authorization::Basic { .. } => "Basic",
+            Authorization::Bearer(_) => "Bearer",
+            Authorization::Other { scheme, .. } => scheme,
+        }
+    }
+}
+
+// Credentials are kept out of the logs.
+impl fmt::Debug for Authorization {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Authorization::Basic { user, .. } => f.debug_struct("Basic").field("user", user).finish_non_exhaustive(),
+            _ => write!(f, "{}(..)", self.scheme()),
+        }
+    }
+}
+
+
+/// Value of the WWW-Authenticate header sent with a 401 Unauthorized, asking for the Basic scheme.
+pub fn basic_challenge(realm: &str) -> String {
+    format!("Basic realm=\"{}\", charset=\"UTF-8\"", quote(realm))
+}
+
+/// Value of the WWW-Authenticate header sent with a 401 Unauthorized, asking for the Bearer scheme.
+/// The error is omitted when the Request held no token, and is `invalid_token` otherwise. (RFC 6750 - 3)
+pub fn bearer_challenge(realm: &str, error: Option<&str>) -> String {
+    match error {
+        Some(error) => format!("Bearer realm=\"{}\", error=\"{}\"", quote(realm), quote(error)),
+        None => format!("Bearer realm=\"{}\"", quote(realm)),
+    }
+}
+
+// Escape a value written in a quoted-string. (RFC 9110 - 5.6.4)
+fn quote(value: &str) -> String {
+    value.replace('\\', "\\\\").replace('"', "\\\"")
+}
+
+
+#[cfg(test)]
+mod test {
+
+    use super::*;
+
+    #[test]
+    fn basic() {
+        assert_eq!(Some(Authorization::Basic { user: "Aladdin".into(), password: "open: sesame".into() }), Authorization::parse("basic QWxhZGRpbjpvcGVuOiBzZXNhbWU="));
+        assert_eq!(None, Authorization::parse("Basic QWxhZGRpbg=="));
+        assert_eq!(None, Authorization::parse("Basic !!"));
+    }
+
+    #[test]
+    fn bearer_and_other() {
+        assert_eq!(Some(Authorization::Bearer("mF_9.B5f-4.1JqM".into())), Authorization::parse("Bearer mF_9.B5f-4.1JqM"));
+        assert_eq!(None, Authorization::parse("Bearer "));
+        assert_eq!(Some(Authorization::Other { scheme: "Digest".into(), credentials: "username=\"Mufasa\"".into() }), Authorization::parse("Digest username=\"Mufasa\""));
+        assert!(!format!("{:?}", Authorization::Basic { user: "Aladdin".into(), password: "secret".into() }).contains("secret"));
+    }
+
+    #[test]
+    fn challenges() {
+        assert_eq!("Basic realm=\"Arkos \\\"admin\\\"\", charset=\"UTF-8\"", basic_challenge("Arkos \"admin\""));
+        assert_eq!("Bearer realm=\"api\", error=\"invalid_token\"", bearer_challenge("api", Some("invalid_token")));
+    }
+
+}
@@ -0,0 +1,256 @@
+use std::fmt;
+use std::fs::File;
+use std::io::{self, Read, Write};
+use std::net::TcpStream;
+use std::ops::Range;
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+
+
+/// Source of a body sent while it is read, as a large file or a generated report, instead of being buffered in memory.
+/// It is sent with `Transfer-Encoding: chunked` on HTTP/1.1, and delimited by the closing of the connection on HTTP/1.0.
+#[derive(Clone)]
+pub struct BodyStream(Arc<Mutex<Box<dyn Read + Send>>>);
+
+impl BodyStream {
+
+    /// Will wrap any source implementing Read, as a File or a BufReader.
+    pub fn new<R: Read + Send + 'static>(reader: R) -> Self {
+        BodyStream(Arc::new(Mutex::new(Box::new(reader))))
+    }
+
+    /// Will read the next bytes of the source into the buffer. Returns 0 once the source is exhausted.
+    pub fn read(&self, buf: &mut [u8]) -> std::io::Result<usize> {
+        self.0.lock().unwrap().read(buf)
+    }
+}
+
+impl fmt::Debug for BodyStream {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("BodyStream")
+    }
+}
+
+// Two streams are equal only if they share the same source.
+impl PartialEq for BodyStream {
+    fn eq(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.0, &other.0)
+    }
+}
+
+impl Eq for BodyStream {}
+
+
+// Size of the buffer a file body is copied through.
+const COPY_BUFFER_SIZE: usize = 64 * 1024;
+
+/// File sent as a body without being loaded in memory, as a video or a download.
+/// On Linux, it is transferred to the connection by the kernel with `sendfile`. Elsewhere, it is copied through a buffer.
+/// The file is read at the positions of the body, so clones and slices sharing it can be sent at the same time.
+#[derive(Clone, Debug)]
+pub struct BodyFile {
+    file: Arc<File>,
+    offset: u64,
+    length: u64,
+}
+
+impl BodyFile {
+
+    /// Will open the file to send it whole.
+    pub fn open<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+        Self::new(File::open(path)?)
+    }
+
+    /// Will send the file whole, from its start.
+    pub fn new(file: File) -> io::Result<Self> {
+        let length = file.metadata()?.len();
+        Ok(BodyFile { file: Arc::new(file), offset: 0, length })
+    }
+
+    /// Number of bytes sent.
+    pub fn len(&self) -> u64 {
+        self.length
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.length == 0
+    }
+
+    /// Will return the part of this body in the range, as a 206 Partial Content sends it.
+    pub fn slice(&self, range: Range<u64>) -> BodyFile {
+        let start = range.start.min(self.length);
+        let end = range.end.clamp(start, self.length);
+        BodyFile { file: Arc::clone(&self.file), offset: self.offset + start, length: end - start }
+    }
+
+    /// Will read the body in memory.
+    pub fn read_all(&self) -> io::Result<Vec<u8>> {
+        let mut bytes = Vec::with_capacity(self.length as usize);
+        self.copy_to(&mut bytes)?;
+        Ok(bytes)
+    }
+
+    /// Will copy the body to the writer through a buffer.
+    pub fn copy_to<W: Write + ?Sized>(&self, writer: &mut W) -> io::Result<()> {
+        let mut buffer = vec![0; COPY_BUFFER_SIZE.min(self.length as usize)];
+        let mut position = self.offset;
+        let end = self.offset + self.length;
+        while position < end {
+            let count = buffer.len().min((end - position) as usize);
+            let read = match self.read_at(&mut buffer[..count], position) {
+                Ok(0) => return Err(io::Error::new(io::ErrorKind::UnexpectedEof, "the file has been truncated while being sent")),
+                Ok(read) => read,
+                Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
+                Err(e) => return Err(e),
+            };
+            writer.write_all(&buffer[..read])?;
+            position += read as u64;
+        }
+        Ok(())
+    }
+
+    // Positional read: the cursor of the file, shared by the clones, is not used.
+    #[cfg(unix)]
+    fn read_at(&self, buf: &mut [u8], position: u64) -> io::Result<usize> {
+        std::os::unix::fs::FileExt::read_at(&*self.file, buf, position)
+    }
+
+    // seek_read moves the cursor, but reads at the position given whatever it is.
+    #[cfg(windows)]
+    fn read_at(&self, buf: &mut [u8], position: u64) -> io::Result<usize> {
+        std::os::windows::fs::FileExt::seek_read(&*self.file, buf, position)
+    }
+
+    #[cfg(not(any(unix, windows)))]
+    fn read_at(&self, buf: &mut [u8], position: u64) -> io::Result<usize> {
+        use std::io::{Seek, SeekFrom};
+        let mut file = &*self.file;
+        file.seek(SeekFrom::Start(position))?;
+        file.read(buf)
+    }
+
+    /// Will send the body to the connection. On Linux, the kernel transfers the file without copying it in user space.
+    pub fn send_to(&self, stream: &mut TcpStream) -> io::Result<()> {
+        #[cfg(target_os = "linux")]
+        {
+            self.sendfile(stream)
+        }
+        #[cfg(not(target_os = "linux"))]
+        {
+            self.copy_to(stream)
+        }
+    }
+
+    #[cfg(target_os = "linux")]
+    fn sendfile(&self, stream: &mut TcpStream) -> io::Result<()> {
+        use std::os::unix::io::AsRawFd;
+
+        let mut offset = self.offset as libc::off_t;
+        let mut remaining = self.length;
+        while remaining > 0 {
+            // sendfile transfers at most 0x7ffff000 bytes at once.
+            let count = remaining.min(0x7fff_f000) as usize;
+            let sent = unsafe { libc::sendfile(stream.as_raw_fd(), self.file.as_raw_fd(), &mut offset, count) };
+            if sent < 0 {
+                let error = io::Error::last_os_error();
+                match error.raw_os_error() {
+                    Some(libc::EINTR) => continue,
+                    // The file system doesn't support it: nothing has been sent yet.
+                    Some(libc::EINVAL) | Some(libc::ENOSYS) if remaining == self.length => return self.copy_to(stream),
+                    // EAGAIN, a non-blocking connection being full or its write timeout reached, is returned as WouldBlock.
+                    _ => return Err(error),
+                }
+            }
+            if sent == 0 {
+                return Err(io::Error::new(io::ErrorKind::UnexpectedEof, "the file has been truncated while being sent"));
+            }
+            remaining -= sent as u64;
+        }
+        Ok(())
+    }
+}
+
+// Two bodies are equal only if they send the same part of the same opened file.
+impl PartialEq for BodyFile {
+    fn eq(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.file, &other.file) && self.offset == other.offset && self.length == other.length
+    }
+}
+
+impl Eq for BodyFile {}
+
+
+#[cfg(test)]
+mod test {
+
+    use super::*;
+
+    #[test]
+    fn file_slices() {
+        let path = std::env::temp_dir().join(format!("arkos-body-{}", std::process::id()));
+        std::fs::write(&path, "0123456789").unwrap();
+        let body = BodyFile::open(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        assert_eq!(10, body.len());
+        assert_eq!(b"0123456789".to_vec(), body.read_all().unwrap());
+        let slice = body.slice(2..8);
+        assert_eq!(b"234567".to_vec(), slice.read_all().unwrap());
+        assert_eq!(b"45".to_vec(), slice.slice(2..4).read_all().unwrap());
+        assert!(body.slice(8..20).len() == 2 && body.slice(12..20).is_empty());
+    }
+
+    #[test]
+    fn send_to_connection() {
+        let path = std::env::temp_dir().join(format!("arkos-sendfile-{}", std::process::id()));
+        let content: Vec<u8> = (0..200_000u32).map(|i| (i % 251) as u8).collect();
+        std::fs::write(&path, &content).unwrap();
+        let body = BodyFile::open(&path).unwrap().slice(1000..150_000);
+        std::fs::remove_file(&path).unwrap();
+
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let address = listener.local_addr().unwrap();
+        let reader = std::thread::spawn(move || {
+            let mut received = Vec::new();
+            TcpStream::connect(address).unwrap().read_to_end(&mut received).unwrap();
+            received
+        });
+        let (mut stream, _) = listener.accept().unwrap();
+        body.send_to(&mut stream).unwrap();
+        drop(stream);
+        assert_eq!(content[1000..150_000].to_vec(), reader.join().unwrap());
+    }
+
+    #[test]
+    fn clones_are_read_concurrently() {
+        let path = std::env::temp_dir().join(format!("arkos-concurrent-{}", std::process::id()));
+        let content: Vec<u8> = (0..300_000u32).map(|i| (i % 251) as u8).collect();
+        std::fs::write(&path, &content).unwrap();
+        let body = BodyFile::open(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        let readers: Vec<_> = (0..8u64).map(|i| {
+            let slice = body.slice(i * 1000..i * 1000 + 200_000);
+            std::thread::spawn(move || (0..10).map(|_| slice.read_all().unwrap()).collect::<Vec<Vec<u8>>>())
+        }).collect();
+        for (i, reader) in readers.into_iter().enumerate() {
+            assert!(reader.join().unwrap().iter().all(|read| read[..] == content[i * 1000..i * 1000 + 200_000]));
+        }
+    }
+
+    #[test]
+    fn full_connection_is_not_waited_for() {
+        let path = std::env::temp_dir().join(format!("arkos-nonblocking-{}", std::process::id()));
+        std::fs::write(&path, vec![0u8; 32 * 1024 * 1024]).unwrap();
+        let body = BodyFile::open(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let _client = TcpStream::connect(listener.local_addr().unwrap()).unwrap();
+        let (mut stream, _) = listener.accept().unwrap();
+        stream.set_nonblocking(true).unwrap();
+        // The client never reads: the connection fills up.
+        assert_eq!(io::ErrorKind::WouldBlock, body.send_to(&mut stream).unwrap_err().kind());
+    }
+
+}
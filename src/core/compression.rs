@@ -0,0 +1,230 @@
+use std::io;
+
+
+/// Content coding compressing a body. Each one is available with the cargo feature of the same name:
+/// `gzip`, `deflate`, `brotli` and `zstd`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Encoding {
+    Gzip,
+    Deflate,
+    Brotli,
+    Zstd,
+}
+
+impl Encoding {
+
+    /// Every content coding compiled in, in the order the server prefers them.
+    pub fn available() -> Vec<Encoding> {
+        [Encoding::Brotli, Encoding::Zstd, Encoding::Gzip, Encoding::Deflate].iter().copied().filter(Encoding::is_available).collect()
+    }
+
+    /// Will return true if the feature of the content coding is enabled.
+    pub fn is_available(&self) -> bool {
+        match self {
+            Encoding::Gzip => cfg!(feature = "gzip"),
+            Encoding::Deflate => cfg!(feature = "deflate"),
+            Encoding::Brotli => cfg!(feature = "brotli"),
+            Encoding::Zstd => cfg!(feature = "zstd"),
+        }
+    }
+
+    /// Name of the content coding in Accept-Encoding and Content-Encoding headers.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Encoding::Gzip => "gzip",
+            Encoding::Deflate => "deflate",
+            Encoding::Brotli => "br",
+            Encoding::Zstd => "zstd",
+        }
+    }
+
+    /// Will parse the name of a content coding. `x-gzip` is gzip. (RFC 9110 - 8.4.1.3)
+    pub fn from_name(name: &str) -> Option<Encoding> {
+        match name.trim().to_ascii_lowercase().as_str() {
+            "gzip" | "x-gzip" => Some(Encoding::Gzip),
+            "deflate" => Some(Encoding::Deflate),
+            "br" => Some(Encoding::Brotli),
+            "zstd" => Some(Encoding::Zstd),
+            _ => None,
+        }
+    }
+
+    /// Will compress the bytes. Returns an error of kind Unsupported if the feature is not enabled.
+    pub fn encode(&self, bytes: &[u8]) -> io::Result<Vec<u8>> {
+        match self {
+            #[cfg(feature = "gzip")]
+            Encoding::Gzip => {
+                use std::io::Write;
+                let mut encoder = flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::default());
+                encoder.write_all(bytes)?;
+                encoder.finish()
+            }
+            #[cfg(feature = "deflate")]
+            Encoding::Deflate => {
+                // The deflate coding is the zlib format. (RFC 9110 - 8.4.1.2)
+                use std::io::Write;
+                let mut encoder = flate2::write::ZlibEncoder::new(Vec::new(), flate2::Compression::default());
+                encoder.write_all(bytes)?;
+                encoder.finish()
+            }
+            #[cfg(feature = "brotli")]
+            Encoding::Brotli => {
+                use std::io::Write;
+                let mut encoder = brotli::CompressorWriter::new(Vec::new(), 4096, 5, 22);
+                encoder.write_all(bytes)?;
+                Ok(encoder.into_inner())
+            }
+            #[cfg(feature = "zstd")]
+            Encoding::Zstd => zstd::encode_all(bytes, 3),
+            #[allow(unreachable_patterns)]
+            _ => {
+                let _ = bytes;
+                Err(unsupported(self))
+            }
+        }
+    }
+
+    /// Will return a reader decompressing the bytes, to bound the size read from it.
+    /// Returns an error of kind Unsupported if the feature is not enabled.
+    pub fn decoder<'a>(&self, bytes: &'a [u8]) -> io::Result<Box<dyn io::Read + 'a>> {
+        match self {
+            #[cfg(feature = "gzip")]
+            Encoding::Gzip => Ok(Box::new(flate2::read::MultiGzDecoder::new(bytes))),
+            #[cfg(feature = "deflate")]
+            Encoding::Deflate => {
+                // Some clients send a raw deflate stream instead of the zlib format: its header is then missing.
+                let zlib = bytes.len() >= 2 && bytes[0] & 0x0f == 8 && (u16::from(bytes[0]) << 8 | u16::from(bytes[1])) % 31 == 0;
+                match zlib {
+                    true => Ok(Box::new(flate2::read::ZlibDecoder::new(bytes))),
+                    false => Ok(Box::new(flate2::read::DeflateDecoder::new(bytes))),
+                }
+            }
+            #[cfg(feature = "brotli")]
+            Encoding::Brotli => Ok(Box::new(brotli::Decompressor::new(bytes, 4096))),
+            #[cfg(feature = "zstd")]
+            Encoding::Zstd => Ok(Box::new(zstd::stream::read::Decoder::with_buffer(bytes)?)),
+            #[allow(unreachable_patterns)]
+            _ => {
+                let _ = bytes;
+                Err(unsupported(self))
+            }
+        }
+    }
+}
+
+fn unsupported(encoding: &Encoding) -> io::Error {
+    io::Error::new(io::ErrorKind::Unsupported, format!("the feature of the {} content coding is not enabled", encoding.name()))
+}
+
+
+/// Compression of the Responses, set on the Server and overridden on a Route.
+/// A body is compressed with the content coding the client prefers, if its Content-Type is compressible and it is large enough.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Compression {
+    #[doc(hidden)]
+    pub encodings: Vec<Encoding>,
+    #[doc(hidden)]
+    pub min_size: usize,
+}
+
+impl Compression {
+
+    /// Will compress with every content coding compiled in, bodies of at least 1024 bytes.
+    pub fn new() -> Self {
+        Compression { encodings: Encoding::available(), min_size: 1024 }
+    }
+
+    /// Will never compress.
+    pub fn disabled() -> Self {
+        Compression { encodings: Vec::new(), min_size: 0 }
+    }
+
+    /// Will compress only with these content codings, in this order of preference. Those whose feature is not enabled are ignored.
+    pub fn encodings(mut self, encodings: &[Encoding]) -> Self {
+        self.encodings = encodings.iter().copied().filter(Encoding::is_available).collect();
+        self
+    }
+
+    /// Smaller bodies are sent uncompressed: their headers would outweigh the gain.
+    pub fn min_size(mut self, min_size: usize) -> Self {
+        self.min_size = min_size;
+        self
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        !self.encodings.is_empty()
+    }
+}
+
+impl Default for Compression {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Will return true for the Content-Types worth compressing: text, JSON, XML, JavaScript, SVG and WebAssembly.
+/// Images, videos, archives and fonts as WOFF are already compressed.
+pub fn is_compressible(content_type: &str) -> bool {
+    let essence = content_type.split(';').next().unwrap_or_default().trim().to_ascii_lowercase();
+    essence.starts_with("text/")
+        || essence.ends_with("/json") || essence.ends_with("+json")
+        || essence.ends_with("/xml") || essence.ends_with("+xml")
+        || essence.contains("javascript")
+        || matches!(essence.as_str(), "application/wasm" | "application/x-www-form-urlencoded" | "font/ttf" | "font/otf" | "image/x-icon" | "image/bmp")
+}
+
+
+#[cfg(test)]
+mod test {
+
+    use super::*;
+
+    #[test]
+    fn names() {
+        assert_eq!(Some(Encoding::Gzip), Encoding::from_name("X-Gzip"));
+        assert_eq!(Some(Encoding::Brotli), Encoding::from_name("br"));
+        assert_eq!(None, Encoding::from_name("compress"));
+        assert_eq!("zstd", Encoding::Zstd.name());
+    }
+
+    #[test]
+    fn compressible() {
+        assert!(is_compressible("application/json"));
+        assert!(is_compressible("text/html; charset=utf-8"));
+        assert!(is_compressible("application/problem+json"));
+        assert!(is_compressible("image/svg+xml"));
+        assert!(!is_compressible("image/png"));
+        assert!(!is_compressible("application/zip"));
+        assert!(!is_compressible("font/woff2"));
+    }
+
+    #[cfg(all(feature = "gzip", feature = "deflate", feature = "brotli", feature = "zstd"))]
+    #[test]
+    fn encode() {
+        use std::io::Read;
+
+        let body = "{\"name\": \"arkos\"}".repeat(100);
+        let gzip = Encoding::Gzip.encode(body.as_bytes()).unwrap();
+        assert!(gzip.len() < body.len());
+        let mut decoded = String::new();
+        flate2::read::GzDecoder::new(gzip.as_slice()).read_to_string(&mut decoded).unwrap();
+        assert_eq!(body, decoded);
+
+        let deflate = Encoding::Deflate.encode(body.as_bytes()).unwrap();
+        let mut decoded = String::new();
+        flate2::read::ZlibDecoder::new(deflate.as_slice()).read_to_string(&mut decoded).unwrap();
+        assert_eq!(body, decoded);
+
+        let br = Encoding::Brotli.encode(body.as_bytes()).unwrap();
+        let mut decoded = String::new();
+        brotli::Decompressor::new(br.as_slice(), 4096).read_to_string(&mut decoded).unwrap();
+        assert_eq!(body, decoded);
+
+        let zstd = Encoding::Zstd.encode(body.as_bytes()).unwrap();
+        assert_eq!(body.as_bytes(), zstd::decode_all(zstd.as_slice()).unwrap().as_slice());
+
+        assert_eq!(vec![Encoding::Brotli, Encoding::Zstd, Encoding::Gzip, Encoding::Deflate], Compression::new().encodings);
+        assert_eq!(vec![Encoding::Gzip], Compression::new().encodings(&[Encoding::Gzip]).encodings);
+    }
+
+}
@@ -0,0 +1,104 @@
+use crate::core::date::HttpDate;
+use crate::core::etag::{ETag, ETagCondition};
+use crate::core::header::HeaderMap;
+use crate::core::method::HttpMethod;
+use crate::core::status::StatusCode;
+
+
+/// Validators of the current representation of a resource, compared with the preconditions of a Request.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Validators {
+    pub etag: Option<ETag>,
+    pub last_modified: Option<HttpDate>,
+}
+
+impl Validators {
+
+    pub fn new(etag: Option<ETag>, last_modified: Option<HttpDate>) -> Self {
+        Validators { etag, last_modified }
+    }
+
+    /// Will evaluate the If-Match, If-Unmodified-Since, If-None-Match and If-Modified-Since headers, in this order. (RFC 9110 - 13.2.2)
+    /// Returns a 304 Not Modified if a GET or a HEAD can be answered without the representation,
+    /// and a 412 Precondition Failed if a precondition is not met.
+    pub fn evaluate(&self, method: HttpMethod, headers: &HeaderMap) -> Result<(), StatusCode> {
+        let safe = matches!(method, HttpMethod::GET | HttpMethod::HEAD);
+        let date = |name: &str| headers.get(name).and_then(HttpDate::parse);
+
+        match ETagCondition::parse(&headers.get_all("If-Match")) {
+            Some(condition) if !condition.matches(self.etag.as_ref(), ETag::strong_eq) => return Err(StatusCode::PreconditionFailed),
+            Some(_) => (),
+            None => {
+                if let (Some(since), Some(last_modified)) = (date("If-Unmodified-Since"), self.last_modified) {
+                    if last_modified > since {
+                        return Err(StatusCode::PreconditionFailed);
+                    }
+                }
+            }
+        }
+
+        match ETagCondition::parse(&headers.get_all("If-None-Match")) {
+            Some(condition) if condition.matches(self.etag.as_ref(), ETag::weak_eq) => match safe {
+                true => Err(StatusCode::NotModified),
+                false => Err(StatusCode::PreconditionFailed),
+            },
+            Some(_) => Ok(()),
+            None => match (date("If-Modified-Since"), self.last_modified) {
+                (Some(since), Some(last_modified)) if safe && last_modified <= since => Err(StatusCode::NotModified),
+                _ => Ok(()),
+            },
+        }
+    }
+}
+
+
+#[cfg(test)]
+mod test {
+
+    use super::*;
+
+    fn headers(name: &str, value: &str) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        headers.append(name, value);
+        headers
+    }
+
+    const MODIFIED: &str = "Sun, 06 Nov 1994 08:49:37 GMT";
+    const BEFORE: &str = "Sat, 05 Nov 1994 08:49:37 GMT";
+
+    fn validators() -> Validators {
+        Validators::new(Some(ETag::strong("v2")), HttpDate::parse(MODIFIED))
+    }
+
+    #[test]
+    fn if_none_match() {
+        assert_eq!(Err(StatusCode::NotModified), validators().evaluate(HttpMethod::GET, &headers("If-None-Match", "W/\"v1\", W/\"v2\"")));
+        assert_eq!(Err(StatusCode::PreconditionFailed), validators().evaluate(HttpMethod::PUT, &headers("If-None-Match", "*")));
+        assert_eq!(Ok(()), validators().evaluate(HttpMethod::GET, &headers("If-None-Match", "\"v1\"")));
+    }
+
+    #[test]
+    fn if_match() {
+        assert_eq!(Ok(()), validators().evaluate(HttpMethod::PUT, &headers("If-Match", "\"v2\"")));
+        assert_eq!(Err(StatusCode::PreconditionFailed), validators().evaluate(HttpMethod::PUT, &headers("If-Match", "\"v1\"")));
+        assert_eq!(Err(StatusCode::PreconditionFailed), validators().evaluate(HttpMethod::PUT, &headers("If-Match", "W/\"v2\"")));
+        assert_eq!(Err(StatusCode::PreconditionFailed), Validators::default().evaluate(HttpMethod::DELETE, &headers("If-Match", "\"v2\"")));
+    }
+
+    #[test]
+    fn dates() {
+        assert_eq!(Err(StatusCode::NotModified), validators().evaluate(HttpMethod::GET, &headers("If-Modified-Since", MODIFIED)));
+        assert_eq!(Ok(()), validators().evaluate(HttpMethod::GET, &headers("If-Modified-Since", BEFORE)));
+        assert_eq!(Ok(()), validators().evaluate(HttpMethod::POST, &headers("If-Modified-Since", MODIFIED)));
+        assert_eq!(Err(StatusCode::PreconditionFailed), validators().evaluate(HttpMethod::PUT, &headers("If-Unmodified-Since", BEFORE)));
+        assert_eq!(Ok(()), validators().evaluate(HttpMethod::PUT, &headers("If-Unmodified-Since", "invalid date")));
+    }
+
+    #[test]
+    fn etag_takes_precedence_over_dates() {
+        let mut headers = headers("If-None-Match", "\"v1\"");
+        headers.append("If-Modified-Since", MODIFIED);
+        assert_eq!(Ok(()), validators().evaluate(HttpMethod::GET, &headers));
+    }
+
+}
@@ -5,6 +5,7 @@
 pub enum ContentType {
     Json,
     Text,
+    FormUrlEncoded,
     Custom(String)
 }
 
@@ -13,9 +14,66 @@ impl ContentType {
         let msg = match self {
             ContentType::Json => "application/json",
             ContentType::Text => "text/plain",
+            ContentType::FormUrlEncoded => "application/x-www-form-urlencoded",
             ContentType::Custom(s) => s,
             // _ => "text/plain",
         };
         msg.into()
     }
+
+    /// Will parse the value of a Content-Type header. Parameters, as the charset, are ignored.
+    pub fn from_header(value: &str) -> ContentType {
+        let essence = value.split(';').next().unwrap_or_default().trim().to_ascii_lowercase();
+        match essence.as_str() {
+            "application/json" => ContentType::Json,
+            "text/plain" => ContentType::Text,
+            "application/x-www-form-urlencoded" => ContentType::FormUrlEncoded,
+            _ => ContentType::Custom(essence),
+        }
+    }
+
+    /// Will guess the Content-Type of a file from its extension. Unknown extensions are application/octet-stream.
+    pub fn from_extension(extension: &str) -> ContentType {
+        let mime = match extension.to_ascii_lowercase().as_str() {
+            "json" | "map" => return ContentType::Json,
+            "txt" => return ContentType::Text,
+            "html" | "htm" => "text/html; charset=utf-8",
+            "css" => "text/css; charset=utf-8",
+            "js" | "mjs" => "text/javascript; charset=utf-8",
+            "csv" => "text/csv; charset=utf-8",
+            "md" => "text/markdown; charset=utf-8",
+            "xml" => "application/xml",
+            "wasm" => "application/wasm",
+            "pdf" => "application/pdf",
+            "zip" => "application/zip",
+            "webmanifest" => "application/manifest+json",
+            "png" => "image/png",
+            "jpg" | "jpeg" => "image/jpeg",
+            "gif" => "image/gif",
+            "svg" => "image/svg+xml",
+            "ico" => "image/x-icon",
+            "webp" => "image/webp",
+            "avif" => "image/avif",
+            "woff" => "font/woff",
+            "woff2" => "font/woff2",
+            "ttf" => "font/ttf",
+            "otf" => "font/otf",
+            "mp3" => "audio/mpeg",
+            "ogg" => "audio/ogg",
+            "wav" => "audio/wav",
+            "mp4" => "video/mp4",
+            "webm" => "video/webm",
+            _ => "application/octet-stream",
+        };
+        ContentType::Custom(mime.into())
+    }
+
+    /// Will return true for application/json and any structured syntax suffix +json (RFC 6839).
+    pub fn is_json(&self) -> bool {
+        match self {
+            ContentType::Json => true,
+            ContentType::Custom(s) => s.ends_with("+json"),
+            _ => false,
+        }
+    }
 }
\ No newline at end of file
@@ -3,6 +3,8 @@
 
 use log::trace;
 
+use super::date::HttpDate;
+
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct Cookie {
     pub name: String,
@@ -38,6 +40,11 @@ impl Cookie {
         self.expires = exp;
     }
 
+    /// Set when the cookie is due to expired, as an IMF-fixdate: `cookie.expires_at(HttpDate::from_unix(now + 3600))`.
+    pub fn expires_at(&mut self, date: HttpDate) {
+        self.expires = date.to_string();
+    }
+
     pub fn has_max_age(&mut self, age: u32) {
         self.max_age = age; 
     }
@@ -166,4 +173,11 @@ mod test {
         assert_eq!(cookie.is_valid(), false)
     }
 
-}
\ No newline at end of file
+    #[test]
+    fn expires_at_date(){
+        let mut cookie = Cookie::new("Cookie".into(), "Value".into());
+        cookie.expires_at(HttpDate::from_unix(784111777));
+        assert_eq!("Sun, 06 Nov 1994 08:49:37 GMT", cookie.expires)
+    }
+
+}
@@ -0,0 +1,236 @@
+use std::fmt;
+use std::sync::Mutex;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+
+const DAYS: [&str; 7] = ["Mon", "Tue", "Wed", "Thu", "Fri", "Sat", "Sun"];
+const FULL_DAYS: [&str; 7] = ["Monday", "Tuesday", "Wednesday", "Thursday", "Friday", "Saturday", "Sunday"];
+const MONTHS: [&str; 12] = ["Jan", "Feb", "Mar", "Apr", "May", "Jun", "Jul", "Aug", "Sep", "Oct", "Nov", "Dec"];
+
+
+// Date header of the current second, shared by every Response.
+static DATE_HEADER: Mutex<(u64, String)> = Mutex::new((u64::MAX, String::new()));
+
+
+/// Date of an HTTP header, as Date, Last-Modified or If-Modified-Since, or of the Expires attribute of a Cookie, with a precision of one second.
+/// It is written in the IMF-fixdate format: `Sun, 06 Nov 1994 08:49:37 GMT`, and read from the legacy formats too. (RFC 9110 - 5.6.7)
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct HttpDate {
+    secs: u64,
+}
+
+impl HttpDate {
+
+    /// Will return the current date.
+    pub fn now() -> Self {
+        Self::from(SystemTime::now())
+    }
+
+    /// Will return the current date as an IMF-fixdate. It is formatted once per second: every Response carries it in its Date header.
+    pub fn now_header() -> String {
+        let now = Self::now();
+        let mut cached = DATE_HEADER.lock().unwrap_or_else(|e| e.into_inner());
+        if cached.0 != now.secs {
+            *cached = (now.secs, now.to_string());
+        }
+        cached.1.clone()
+    }
+
+    /// Will return the date at this number of seconds since the Unix epoch.
+    pub fn from_unix(secs: u64) -> Self {
+        HttpDate { secs }
+    }
+
+    /// Number of seconds since the Unix epoch.
+    pub fn unix(&self) -> u64 {
+        self.secs
+    }
+
+    /// Will parse an IMF-fixdate, or a date in the obsolete RFC 850 and asctime formats. Returns None if the date is malformed or invalid.
+    pub fn parse(value: &str) -> Option<HttpDate> {
+        let value = value.trim();
+        Self::parse_imf_fixdate(value).or_else(|| Self::parse_rfc850(value)).or_else(|| Self::parse_asctime(value))
+    }
+
+    // Sun, 06 Nov 1994 08:49:37 GMT
+    fn parse_imf_fixdate(value: &str) -> Option<HttpDate> {
+        let (day_name, rest) = value.split_once(", ")?;
+        let parts: Vec<&str> = rest.split(' ').collect();
+        match parts.as_slice() {
+            [day, month, year, time, "GMT"] if day.len() == 2 && year.len() == 4 => {
+                let date = Self::from_parts(year.parse().ok()?, month_number(month)?, day.parse().ok()?, time)?;
+                (DAYS[date.weekday()] == day_name).then_some(date)
+            }
+            _ => None,
+        }
+    }
+
+    // Sunday, 06-Nov-94 08:49:37 GMT
+    fn parse_rfc850(value: &str) -> Option<HttpDate> {
+        let (day_name, rest) = value.split_once(", ")?;
+        let parts: Vec<&str> = rest.split(' ').collect();
+        let (date, time) = match parts.as_slice() {
+            [date, time, "GMT"] => (date.split('-').collect::<Vec<&str>>(), time),
+            _ => return None,
+        };
+        match date.as_slice() {
+            [day, month, year] if day.len() == 2 && year.len() == 2 => {
+                let date = Self::from_parts(full_year(year.parse().ok()?, civil_from_days(HttpDate::now().secs / 86400).0), month_number(month)?, day.parse().ok()?, time)?;
+                (FULL_DAYS[date.weekday()] == day_name).then_some(date)
+            }
+            _ => None,
+        }
+    }
+
+    // Sun Nov  6 08:49:37 1994
+    fn parse_asctime(value: &str) -> Option<HttpDate> {
+        let parts: Vec<&str> = value.split_whitespace().collect();
+        match parts.as_slice() {
+            [day_name, month, day, time, year] if !day.is_empty() && day.len() <= 2 && year.len() == 4 => {
+                let date = Self::from_parts(year.parse().ok()?, month_number(month)?, day.parse().ok()?, time)?;
+                (DAYS[date.weekday()] == *day_name).then_some(date)
+            }
+            _ => None,
+        }
+    }
+
+    // Date from its parts, the time being hh:mm:ss.
+    fn from_parts(year: u64, month: u64, day: u64, time: &str) -> Option<HttpDate> {
+        let time: Vec<u64> = time.split(':').map(|t| if t.len() == 2 { t.parse().ok() } else { None }).collect::<Option<_>>()?;
+        let (hour, minute, second) = match time.as_slice() {
+            [h, m, s] if *h < 24 && *m < 60 && *s < 61 => (*h, *m, *s),
+            _ => return None,
+        };
+        if year < 1970 || day == 0 || day > days_in_month(year, month) {
+            return None;
+        }
+        let days = days_from_civil(year, month, day);
+        Some(HttpDate { secs: days * 86400 + hour * 3600 + minute * 60 + second })
+    }
+
+    // 0 for Monday. The 1st of January 1970 was a Thursday.
+    fn weekday(&self) -> usize {
+        ((self.secs / 86400 + 3) % 7) as usize
+    }
+}
+
+impl From<SystemTime> for HttpDate {
+    fn from(time: SystemTime) -> Self {
+        HttpDate { secs: time.duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or_default() }
+    }
+}
+
+impl From<HttpDate> for SystemTime {
+    fn from(date: HttpDate) -> Self {
+        UNIX_EPOCH + Duration::from_secs(date.secs)
+    }
+}
+
+impl fmt::Display for HttpDate {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let (year, month, day) = civil_from_days(self.secs / 86400);
+        let time = self.secs % 86400;
+        write!(f, "{}, {:02} {} {} {:02}:{:02}:{:02} GMT", DAYS[self.weekday()], day, MONTHS[month as usize - 1], year, time / 3600, time % 3600 / 60, time % 60)
+    }
+}
+
+
+// A two-digit year more than 50 years in the future is the most recent past year ending with the same digits. (RFC 9110 - 5.6.7)
+fn full_year(year: u64, current: u64) -> u64 {
+    let year = current / 100 * 100 + year;
+    if year > current + 50 { year - 100 } else { year }
+}
+
+fn month_number(name: &str) -> Option<u64> {
+    MONTHS.iter().position(|m| *m == name).map(|m| m as u64 + 1)
+}
+
+fn days_in_month(year: u64, month: u64) -> u64 {
+    match month {
+        1 | 3 | 5 | 7 | 8 | 10 | 12 => 31,
+        4 | 6 | 9 | 11 => 30,
+        2 if year % 4 == 0 && (year % 100 != 0 || year % 400 == 0) => 29,
+        2 => 28,
+        _ => 0,
+    }
+}
+
+// Days since the Unix epoch of a date of the proleptic Gregorian calendar, from 1970.
+fn days_from_civil(year: u64, month: u64, day: u64) -> u64 {
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year / 400;
+    let year_of_era = year - era * 400;
+    let day_of_year = (153 * ((month + 9) % 12) + 2) / 5 + day - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    era * 146097 + day_of_era - 719468
+}
+
+// Date of the proleptic Gregorian calendar from the days since the Unix epoch.
+fn civil_from_days(days: u64) -> (u64, u64, u64) {
+    let days = days + 719468;
+    let era = days / 146097;
+    let day_of_era = days - era * 146097;
+    let year_of_era = (day_of_era - day_of_era / 1460 + day_of_era / 36524 - day_of_era / 146096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let mp = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = year_of_era + era * 400;
+    (if month <= 2 { year + 1 } else { year }, month, day)
+}
+
+
+#[cfg(test)]
+mod test {
+
+    use super::*;
+
+    #[test]
+    fn imf_fixdate() {
+        let date = HttpDate::parse("Sun, 06 Nov 1994 08:49:37 GMT").unwrap();
+        assert_eq!(784111777, date.unix());
+        assert_eq!("Sun, 06 Nov 1994 08:49:37 GMT", date.to_string());
+        assert_eq!("Thu, 01 Jan 1970 00:00:00 GMT", HttpDate::from_unix(0).to_string());
+        assert_eq!("Thu, 29 Feb 2024 23:59:59 GMT", HttpDate::parse("Thu, 29 Feb 2024 23:59:59 GMT").unwrap().to_string());
+    }
+
+    #[test]
+    fn obsolete_formats() {
+        let date = HttpDate::from_unix(784111777);
+        assert_eq!(Some(date), HttpDate::parse("Sunday, 06-Nov-94 08:49:37 GMT"));
+        assert_eq!(Some(date), HttpDate::parse("Sun Nov  6 08:49:37 1994"));
+        assert_eq!(Some(HttpDate::from_unix(1_583_020_800)), HttpDate::parse("Sun Mar 01 00:00:00 2020"));
+        assert_eq!(None, HttpDate::parse("Monday, 06-Nov-94 08:49:37 GMT"));
+        assert_eq!(None, HttpDate::parse("Sun, 06-Nov-94 08:49:37 GMT"));
+        assert_eq!(None, HttpDate::parse("Sun Nov  6 08:49:37 94"));
+        assert_eq!(1994, full_year(94, 2026));
+        assert_eq!(2076, full_year(76, 2026));
+        assert_eq!(1977, full_year(77, 2026));
+        assert_eq!(2001, full_year(1, 2099));
+    }
+
+    #[test]
+    fn date_header() {
+        let header = HttpDate::now_header();
+        let date = HttpDate::parse(&header).unwrap();
+        assert!(date.unix().abs_diff(HttpDate::now().unix()) <= 1);
+    }
+
+    #[test]
+    fn invalid_dates() {
+        assert_eq!(None, HttpDate::parse("Mon, 06 Nov 1994 08:49:37 GMT"));
+        assert_eq!(None, HttpDate::parse("Fri, 29 Feb 2023 08:49:37 GMT"));
+        assert_eq!(None, HttpDate::parse("Sun, 06 Nov 1994 24:49:37 GMT"));
+        assert_eq!(None, HttpDate::parse("Sun, 6 Nov 1994 08:49:37 GMT"));
+        assert_eq!(None, HttpDate::parse("Sun, 06 Nov 1994 08:49:37 UTC"));
+    }
+
+    #[test]
+    fn round_trip() {
+        for secs in (0..4_102_444_800).step_by(86_399 * 37) {
+            let date = HttpDate::from_unix(secs);
+            assert_eq!(Some(date), HttpDate::parse(&date.to_string()));
+        }
+    }
+
+}
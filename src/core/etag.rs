@@ -0,0 +1,133 @@
+use std::fmt;
+
+
+/// Entity tag of a representation, sent in the ETag header and compared with If-Match and If-None-Match. (RFC 9110 - 8.8.3)
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct ETag {
+    pub weak: bool,
+    pub tag: String,
+}
+
+impl ETag {
+
+    /// Will panic if the tag holds a double quote, a space or a control character, as it is declared by the server.
+    pub fn strong(tag: &str) -> Self {
+        assert!(is_etagc(tag), "Invalid entity tag declared: {}", tag);
+        ETag { weak: false, tag: tag.into() }
+    }
+
+    /// Will panic if the tag holds a double quote, a space or a control character, as it is declared by the server.
+    pub fn weak(tag: &str) -> Self {
+        assert!(is_etagc(tag), "Invalid entity tag declared: {}", tag);
+        ETag { weak: true, tag: tag.into() }
+    }
+
+    /// Will compute a strong ETag from the bytes of the representation, with a 64-bit FNV-1a hash.
+    pub fn from_bytes(bytes: &[u8]) -> Self {
+        let hash = bytes.iter().fold(0xcbf29ce484222325u64, |hash, b| (hash ^ *b as u64).wrapping_mul(0x100000001b3));
+        ETag { weak: false, tag: format!("{:016x}", hash) }
+    }
+
+    /// Will parse an entity tag: `"xyzzy"` or `W/"xyzzy"`.
+    pub fn parse(value: &str) -> Option<ETag> {
+        let value = value.trim();
+        let (weak, quoted) = match value.strip_prefix("W/") {
+            Some(quoted) => (true, quoted),
+            None => (false, value),
+        };
+        let tag = quoted.strip_prefix('"')?.strip_suffix('"')?;
+        is_etagc(tag).then(|| ETag { weak, tag: tag.into() })
+    }
+
+    /// Both tags are strong and identical. Used by If-Match and If-Range.
+    pub fn strong_eq(&self, other: &ETag) -> bool {
+        !self.weak && !other.weak && self.tag == other.tag
+    }
+
+    /// Both tags are identical, weak or not. Used by If-None-Match.
+    pub fn weak_eq(&self, other: &ETag) -> bool {
+        self.tag == other.tag
+    }
+}
+
+impl fmt::Display for ETag {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.weak {
+            true => write!(f, "W/\"{}\"", self.tag),
+            false => write!(f, "\"{}\"", self.tag),
+        }
+    }
+}
+
+// etagc = "!" / %x23-7E / obs-text
+fn is_etagc(tag: &str) -> bool {
+    tag.bytes().all(|b| b == 0x21 || (0x23..=0x7e).contains(&b) || b >= 0x80)
+}
+
+
+/// Value of an If-Match or If-None-Match header: `*` or a list of entity tags.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ETagCondition {
+    Any,
+    Tags(Vec<ETag>),
+}
+
+impl ETagCondition {
+
+    /// Will parse the values of the header. Malformed entity tags are ignored.
+    pub fn parse(values: &[&str]) -> Option<ETagCondition> {
+        let members: Vec<&str> = values.iter().flat_map(|v| v.split(',')).map(str::trim).filter(|v| !v.is_empty()).collect();
+        match members.as_slice() {
+            [] => None,
+            ["*"] => Some(ETagCondition::Any),
+            _ => Some(ETagCondition::Tags(members.into_iter().filter_map(ETag::parse).collect())),
+        }
+    }
+
+    /// Will return true if the condition matches the ETag of the representation, with the comparison provided.
+    /// `*` matches any representation, even without an ETag.
+    pub fn matches(&self, etag: Option<&ETag>, eq: fn(&ETag, &ETag) -> bool) -> bool {
+        match self {
+            ETagCondition::Any => true,
+            ETagCondition::Tags(tags) => etag.is_some_and(|etag| tags.iter().any(|t| eq(t, etag))),
+        }
+    }
+}
+
+
+#[cfg(test)]
+mod test {
+
+    use super::*;
+
+    #[test]
+    fn parse_and_display() {
+        assert_eq!(Some(ETag::strong("xyzzy")), ETag::parse("\"xyzzy\""));
+        assert_eq!(Some(ETag::weak("xyzzy")), ETag::parse("W/\"xyzzy\""));
+        assert_eq!(None, ETag::parse("xyzzy"));
+        assert_eq!(None, ETag::parse("\"xy\"zy\""));
+        assert_eq!("W/\"xyzzy\"", ETag::weak("xyzzy").to_string());
+        assert_eq!("\"af63dc4c8601ec8c\"", ETag::from_bytes(b"a").to_string());
+    }
+
+    #[test]
+    fn comparison() {
+        // RFC 9110 - 8.8.3.2
+        assert!(!ETag::weak("1").strong_eq(&ETag::weak("1")));
+        assert!(ETag::weak("1").weak_eq(&ETag::weak("1")));
+        assert!(!ETag::weak("1").strong_eq(&ETag::strong("1")));
+        assert!(ETag::weak("1").weak_eq(&ETag::strong("1")));
+        assert!(ETag::strong("1").strong_eq(&ETag::strong("1")));
+    }
+
+    #[test]
+    fn condition() {
+        let condition = ETagCondition::parse(&["\"a\", W/\"b\"", "\"c\""]).unwrap();
+        assert!(condition.matches(Some(&ETag::strong("b")), ETag::weak_eq));
+        assert!(!condition.matches(Some(&ETag::strong("b")), ETag::strong_eq));
+        assert!(!condition.matches(None, ETag::weak_eq));
+        assert_eq!(Some(ETagCondition::Any), ETagCondition::parse(&["*"]));
+        assert_eq!(None, ETagCondition::parse(&[]));
+    }
+
+}
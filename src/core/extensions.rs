@@ -0,0 +1,91 @@
+use std::any::{Any, TypeId};
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::Arc;
+
+
+/// Type-keyed map carried by a Request, to pass data from a Checkpoint or a Route check to the Route Response.
+/// e.g. a Checkpoint authenticating the user inserts it, the Response reads it with `request.extension::<User>()`.
+/// It holds at most one value per type.
+#[derive(Clone, Default)]
+pub struct Extensions {
+    #[doc(hidden)]
+    map: HashMap<TypeId, Arc<dyn Any + Send + Sync>>,
+}
+
+impl Extensions {
+
+    pub fn new() -> Self {
+        Extensions { map: HashMap::new() }
+    }
+
+    /// Will insert a value, replacing the previous value of the same type.
+    pub fn insert<T: Send + Sync + 'static>(&mut self, value: T) {
+        self.map.insert(TypeId::of::<T>(), Arc::new(value));
+    }
+
+    /// Will return the value of this type, if inserted.
+    pub fn get<T: Send + Sync + 'static>(&self) -> Option<&T> {
+        self.map.get(&TypeId::of::<T>()).and_then(|v| v.downcast_ref::<T>())
+    }
+
+    /// Will return the value of this type, if inserted, as an Arc shared with the map.
+    pub fn get_arc<T: Send + Sync + 'static>(&self) -> Option<Arc<T>> {
+        self.map.get(&TypeId::of::<T>()).cloned().and_then(|v| v.downcast::<T>().ok())
+    }
+
+    pub fn contains<T: Send + Sync + 'static>(&self) -> bool {
+        self.map.contains_key(&TypeId::of::<T>())
+    }
+
+    /// Will remove the value of this type. Returns true if there was one.
+    pub fn remove<T: Send + Sync + 'static>(&mut self) -> bool {
+        self.map.remove(&TypeId::of::<T>()).is_some()
+    }
+
+    pub fn len(&self) -> usize {
+        self.map.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.map.is_empty()
+    }
+}
+
+impl fmt::Debug for Extensions {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Extensions").field("len", &self.map.len()).finish()
+    }
+}
+
+
+#[cfg(test)]
+mod test {
+
+    use super::*;
+
+    #[derive(Debug, PartialEq)]
+    struct User(String);
+
+    #[test]
+    fn insert_and_get() {
+        let mut extensions = Extensions::new();
+        extensions.insert(User("Bernard".into()));
+        extensions.insert(42u32);
+        assert_eq!(Some(&User("Bernard".into())), extensions.get::<User>());
+        assert_eq!(Some(&42u32), extensions.get::<u32>());
+        assert_eq!(None, extensions.get::<u64>());
+    }
+
+    #[test]
+    fn replace_and_remove() {
+        let mut extensions = Extensions::new();
+        extensions.insert(User("Bernard".into()));
+        extensions.insert(User("Léa".into()));
+        assert_eq!(1, extensions.len());
+        assert_eq!(Some(&User("Léa".into())), extensions.get::<User>());
+        assert!(extensions.remove::<User>());
+        assert!(!extensions.contains::<User>());
+    }
+
+}
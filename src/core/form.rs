@@ -0,0 +1,105 @@
+/// Fields of an application/x-www-form-urlencoded body, as sent by HTML forms.
+/// A key can be repeated (e.g. checkboxes), the fields are kept in the order they were sent.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Form {
+    #[doc(hidden)]
+    fields: Vec<(String, String)>,
+}
+
+impl Form {
+
+    /// Will parse an urlencoded body: `name=Jean+Luc&lang=fr&lang=en`.
+    /// Percent-encoded sequences and '+' are decoded. A field without '=' has an empty value.
+    pub fn parse(raw: &[u8]) -> Form {
+        let fields = raw.split(|b| *b == b'&')
+            .filter(|pair| !pair.is_empty())
+            .map(|pair| {
+                let mut pair = pair.splitn(2, |b| *b == b'=');
+                let key = pair.next().unwrap_or_default();
+                let value = pair.next().unwrap_or_default();
+                (decode(key, true), decode(value, true))
+            })
+            .collect();
+        Form { fields }
+    }
+
+    /// Will return the first value of the field, if present.
+    pub fn get(&self, name: &str) -> Option<&str> {
+        self.fields.iter().find(|(n, _)| n == name).map(|(_, v)| v.as_str())
+    }
+
+    /// Will return every value of the field, in the order they were sent.
+    pub fn get_all(&self, name: &str) -> Vec<&str> {
+        self.fields.iter().filter(|(n, _)| n == name).map(|(_, v)| v.as_str()).collect()
+    }
+
+    pub fn contains_key(&self, name: &str) -> bool {
+        self.fields.iter().any(|(n, _)| n == name)
+    }
+
+    /// Will iterate throught every field, in the order they were sent.
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &str)> {
+        self.fields.iter().map(|(n, v)| (n.as_str(), v.as_str()))
+    }
+
+    pub fn len(&self) -> usize {
+        self.fields.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.fields.is_empty()
+    }
+}
+
+
+// Percent-decoding (RFC 3986 - 2.1). When `plus_as_space` is set, '+' is decoded as a space as in urlencoded forms.
+// Malformed sequences are kept as-is, and invalid UTF-8 is replaced by U+FFFD.
+pub(crate) fn decode(raw: &[u8], plus_as_space: bool) -> String {
+    let hex = |b: u8| -> Option<u8> { (b as char).to_digit(16).map(|d| d as u8) };
+
+    let mut decoded = Vec::with_capacity(raw.len());
+    let mut i = 0;
+    while i < raw.len() {
+        match raw[i] {
+            b'+' if plus_as_space => decoded.push(b' '),
+            b'%' if i + 2 < raw.len() => {
+                match (hex(raw[i + 1]), hex(raw[i + 2])) {
+                    (Some(h), Some(l)) => {
+                        decoded.push(h << 4 | l);
+                        i += 2;
+                    }
+                    _ => decoded.push(b'%'),
+                }
+            }
+            b => decoded.push(b),
+        }
+        i += 1;
+    }
+    String::from_utf8_lossy(&decoded).into_owned()
+}
+
+
+#[cfg(test)]
+mod test {
+
+    use super::*;
+
+    #[test]
+    fn decoding() {
+        let form = Form::parse(b"name=Jean+Luc&city=Saint-%C3%89tienne&rate=100%25&broken=%zz%4");
+        assert_eq!(Some("Jean Luc"), form.get("name"));
+        assert_eq!(Some("Saint-Étienne"), form.get("city"));
+        assert_eq!(Some("100%"), form.get("rate"));
+        assert_eq!(Some("%zz%4"), form.get("broken"));
+    }
+
+    #[test]
+    fn repeated_keys() {
+        let form = Form::parse(b"lang=fr&lang=en&empty&=value");
+        assert_eq!(vec!["fr", "en"], form.get_all("lang"));
+        assert_eq!(Some(""), form.get("empty"));
+        assert_eq!(Some("value"), form.get(""));
+        assert_eq!(4, form.len());
+    }
+
+}
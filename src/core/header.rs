@@ -0,0 +1,161 @@
+use std::str::FromStr;
+
+
+/// Collection of HTTP headers used by both Request and Response.
+/// Names are case-insensitive, a name can hold multiple values and the headers are iterated in insertion order.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct HeaderMap {
+    #[doc(hidden)]
+    entries: Vec<(String, String)>,
+}
+
+impl HeaderMap {
+
+    /// Will return an empty HeaderMap.
+    pub fn new() -> Self {
+        HeaderMap { entries: Vec::new() }
+    }
+
+    /// Will return the first value of the header, if present.
+    pub fn get(&self, name: &str) -> Option<&str> {
+        self.entries.iter().find(|(n, _)| n.eq_ignore_ascii_case(name)).map(|(_, v)| v.as_str())
+    }
+
+    /// Will return every value of the header, in insertion order.
+    pub fn get_all(&self, name: &str) -> Vec<&str> {
+        self.entries.iter().filter(|(n, _)| n.eq_ignore_ascii_case(name)).map(|(_, v)| v.as_str()).collect()
+    }
+
+    /// Will return every value of the header as a list: values received on multiple lines and comma-separated
+    /// values are treated alike. Empty members are ignored.
+    pub fn get_list(&self, name: &str) -> Vec<&str> {
+        self.get_all(name).into_iter().flat_map(|v| v.split(',')).map(str::trim).filter(|v| !v.is_empty()).collect()
+    }
+
+    /// Will parse the first value of the header. Returns None if absent or if it cannot be parsed.
+    pub fn get_as<T: FromStr>(&self, name: &str) -> Option<T> {
+        self.get(name).and_then(|v| v.trim().parse::<T>().ok())
+    }
+
+    /// Will return true if at least one value exists for the header.
+    pub fn contains_key(&self, name: &str) -> bool {
+        self.entries.iter().any(|(n, _)| n.eq_ignore_ascii_case(name))
+    }
+
+    /// Will replace every value of the header by the one provided.
+    /// The header keeps the position of its first occurrence.
+    pub fn insert(&mut self, name: &str, value: &str) {
+        match self.entries.iter().position(|(n, _)| n.eq_ignore_ascii_case(name)) {
+            Some(pos) => {
+                self.entries[pos].1 = value.into();
+                let mut index = 0;
+                self.entries.retain(|(n, _)| {
+                    index += 1;
+                    index - 1 <= pos || !n.eq_ignore_ascii_case(name)
+                });
+            }
+            None => self.entries.push((name.into(), value.into())),
+        }
+    }
+
+    /// Will add a value to the header without removing the existing ones.
+    pub fn append(&mut self, name: &str, value: &str) {
+        self.entries.push((name.into(), value.into()));
+    }
+
+    /// Will remove every value of the header and return them.
+    pub fn remove(&mut self, name: &str) -> Vec<String> {
+        let (removed, kept) = std::mem::take(&mut self.entries).into_iter().partition(|(n, _)| n.eq_ignore_ascii_case(name));
+        self.entries = kept;
+        removed.into_iter().map(|(_, v)| v).collect()
+    }
+
+    /// Will iterate throught every name and value, in insertion order.
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &str)> {
+        self.entries.iter().map(|(n, v)| (n.as_str(), v.as_str()))
+    }
+
+    /// Number of values stored.
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Value of the Content-Length header.
+    pub fn content_length(&self) -> Option<usize> {
+        self.get_as("Content-Length")
+    }
+
+    /// Value of the Content-Type header.
+    pub fn content_type(&self) -> Option<&str> {
+        self.get("Content-Type")
+    }
+
+    /// Value of the Host header.
+    pub fn host(&self) -> Option<&str> {
+        self.get("Host")
+    }
+
+    /// Value of the User-Agent header.
+    pub fn user_agent(&self) -> Option<&str> {
+        self.get("User-Agent")
+    }
+
+}
+
+
+#[cfg(test)]
+mod test {
+
+    use super::*;
+
+    #[test]
+    fn case_insensitive() {
+        let mut headers = HeaderMap::new();
+        headers.append("Content-Type", "text/plain");
+        assert_eq!(Some("text/plain"), headers.get("content-type"));
+        assert!(headers.contains_key("CONTENT-TYPE"));
+    }
+
+    #[test]
+    fn multiple_values() {
+        let mut headers = HeaderMap::new();
+        headers.append("Accept", "text/html");
+        headers.append("accept", "application/json, text/plain");
+        assert_eq!(Some("text/html"), headers.get("Accept"));
+        assert_eq!(vec!["text/html", "application/json, text/plain"], headers.get_all("Accept"));
+        assert_eq!(vec!["text/html", "application/json", "text/plain"], headers.get_list("Accept"));
+    }
+
+    #[test]
+    fn insert_replaces_every_value() {
+        let mut headers = HeaderMap::new();
+        headers.append("X-First", "1");
+        headers.append("X-Value", "a");
+        headers.append("X-Last", "2");
+        headers.append("x-value", "b");
+        headers.insert("X-Value", "c");
+        assert_eq!(vec![("X-First", "1"), ("X-Value", "c"), ("X-Last", "2")], headers.iter().collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn remove() {
+        let mut headers = HeaderMap::new();
+        headers.append("X-Value", "a");
+        headers.append("x-value", "b");
+        assert_eq!(vec!["a".to_string(), "b".to_string()], headers.remove("X-VALUE"));
+        assert!(headers.is_empty());
+    }
+
+    #[test]
+    fn typed_accessors() {
+        let mut headers = HeaderMap::new();
+        headers.append("content-length", " 42");
+        assert_eq!(Some(42), headers.content_length());
+        assert_eq!(None, headers.get_as::<u8>("Host"));
+    }
+
+}
@@ -0,0 +1,140 @@
+//! JSON extraction and responses, available with the `json` feature.
+
+use std::fmt;
+
+use log::debug;
+use serde::{de::DeserializeOwned, Serialize};
+
+use crate::core::{content::ContentType, status::{StatusCode, HttpStatusCode}};
+use crate::server::{request::Request, response::Response};
+
+
+/// Reason why the body of a Request could not be extracted as JSON.
+/// It converts into a StatusCode, so it can be propagated with `?` in a Route Response,
+/// or turned into a Response carrying the message with `into_response`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum JsonRejection {
+    /// The Content-Type is missing or is not JSON - 415 Unsupported Media Type.
+    UnsupportedMediaType(String),
+    /// The body is not valid JSON or doesn't match the expected type - 422 Unprocessable Entity.
+    UnprocessableEntity(String),
+}
+
+impl JsonRejection {
+
+    pub fn status(&self) -> StatusCode {
+        match self {
+            JsonRejection::UnsupportedMediaType(_) => StatusCode::UnsupportedMediaType,
+            JsonRejection::UnprocessableEntity(_) => StatusCode::UnprocessableEntity,
+        }
+    }
+
+    pub fn message(&self) -> &str {
+        match self {
+            JsonRejection::UnsupportedMediaType(m) | JsonRejection::UnprocessableEntity(m) => m,
+        }
+    }
+
+    /// Will generate a Response with the StatusCode and a JSON body: {"error": "<message>"}
+    pub fn into_response(self) -> Response {
+        let mut response = Response::default();
+        response.status = self.status();
+        response.set_body(serde_json::json!({ "error": self.message() }).to_string());
+        response
+    }
+}
+
+impl fmt::Display for JsonRejection {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} {}: {}", self.status().get_code(), self.status().get_title(), self.message())
+    }
+}
+
+impl std::error::Error for JsonRejection {}
+
+impl From<JsonRejection> for StatusCode {
+    fn from(rejection: JsonRejection) -> Self {
+        debug!("JSON extraction failed - {}", rejection);
+        rejection.status()
+    }
+}
+
+
+impl Request {
+
+    /// Will deserialize the body of the Request.
+    /// Returns a 415 Unsupported Media Type if the Content-Type is not JSON, and a 422 Unprocessable Entity
+    /// if the body cannot be deserialized into `T`.
+    pub fn json<T: DeserializeOwned>(&self) -> Result<T, JsonRejection> {
+        match self.content_type() {
+            Some(content_type) if content_type.is_json() => (),
+            Some(content_type) => return Err(JsonRejection::UnsupportedMediaType(format!("Expected a Content-Type of application/json, found {}.", content_type.get()))),
+            None => return Err(JsonRejection::UnsupportedMediaType("Expected a Content-Type of application/json, found none.".into())),
+        }
+
+        serde_json::from_slice(&self.body).map_err(|e| JsonRejection::UnprocessableEntity(format!("Failed to deserialize the body: {}.", e)))
+    }
+}
+
+impl Response {
+
+    /// Will return a 200 Ok Response with the value serialized as body and a Content-Type of application/json.
+    /// If the value fails to be serialized, a 500 Internal Server Error is returned.
+    pub fn json<T: Serialize + ?Sized>(value: &T) -> Result<Response, StatusCode> {
+        let body = serde_json::to_vec(value).map_err(|e| {
+            debug!("Failed to serialize the Response body - {}", e);
+            StatusCode::InternalServerError
+        })?;
+        let mut response = Response::default();
+        response.set_content_type(ContentType::Json);
+        response.set_body_bytes(body);
+        Ok(response)
+    }
+}
+
+
+#[cfg(test)]
+mod test {
+
+    use serde::Deserialize;
+
+    use super::*;
+
+    #[derive(Debug, Deserialize, Serialize, PartialEq)]
+    struct User {
+        name: String,
+        age: u8,
+    }
+
+    fn request(content_type: &str, body: &str) -> Request {
+        Request::parse(format!("POST /users HTTP/1.1\r\nHost: localhost\r\nContent-Type: {}\r\nContent-Length: {}\r\n\r\n{}", content_type, body.len(), body)).unwrap()
+    }
+
+    #[test]
+    fn valid_body() {
+        let user: User = request("application/json; charset=utf-8", r#"{"name": "Bernard", "age": 42}"#).json().unwrap();
+        assert_eq!(User { name: "Bernard".into(), age: 42 }, user);
+    }
+
+    #[test]
+    fn wrong_content_type() {
+        let rejection = request("text/plain", r#"{"name": "Bernard", "age": 42}"#).json::<User>().unwrap_err();
+        assert_eq!(StatusCode::UnsupportedMediaType, StatusCode::from(rejection));
+    }
+
+    #[test]
+    fn invalid_body() {
+        let rejection = request("application/json", r#"{"name": "Bernard"}"#).json::<User>().unwrap_err();
+        assert_eq!(StatusCode::UnprocessableEntity, rejection.status());
+        assert!(rejection.message().contains("missing field `age`"));
+        assert_eq!(StatusCode::UnprocessableEntity, rejection.into_response().status);
+    }
+
+    #[test]
+    fn json_response() {
+        let response = Response::json(&User { name: "Bernard".into(), age: 42 }).unwrap();
+        assert_eq!(ContentType::Json, response.content_type);
+        assert_eq!(Some(r#"{"name":"Bernard","age":42}"#), response.text());
+    }
+
+}
@@ -23,6 +23,14 @@ impl HttpMethod {
         }
     }
 
+    /// Will return None if the method is not taken in charge, instead of falling back to GET.
+    pub fn parse(s: &str) -> Option<HttpMethod> {
+        match s {
+            "HEAD" | "GET" | "POST" | "DELETE" | "PUT" | "OPTIONS" => Some(HttpMethod::from_str(s)),
+            _ => None,
+        }
+    }
+
 }
 
 impl ToString for HttpMethod {
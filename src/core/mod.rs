@@ -1,4 +1,20 @@
 pub mod content;
 pub mod method;
 pub mod status;
-pub mod cookie;
\ No newline at end of file
+pub mod cookie;
+pub mod header;
+pub mod form;
+pub mod multipart;
+pub mod validation;
+pub mod extensions;
+pub mod negotiation;
+pub mod authorization;
+pub mod principal;
+pub mod date;
+pub mod etag;
+pub mod conditional;
+pub mod range;
+pub mod body;
+pub mod compression;
+#[cfg(feature = "json")]
+pub mod json;
\ No newline at end of file
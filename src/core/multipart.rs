@@ -0,0 +1,368 @@
+use std::borrow::Cow;
+use std::fs::{self, File};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use log::{trace, warn};
+
+use crate::core::{header::HeaderMap, status::StatusCode};
+
+
+/// Limits applied when parsing a multipart/form-data body.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct MultipartLimits {
+    /// Largest body accepted - 413 Payload Too Large above.
+    pub max_total_size: usize,
+    /// Largest part accepted - 413 Payload Too Large above.
+    pub max_part_size: usize,
+    /// Parts larger than this are written to a temporary file instead of being kept in memory.
+    pub memory_threshold: usize,
+}
+
+impl Default for MultipartLimits {
+
+    /// 10 MiB for the whole body and for a part, parts above 256 KiB are written to a temporary file.
+    fn default() -> Self {
+        MultipartLimits { max_total_size: 10 * 1024 * 1024, max_part_size: 10 * 1024 * 1024, memory_threshold: 256 * 1024 }
+    }
+}
+
+
+/// Parts of a multipart/form-data body, in the order they were sent.
+#[derive(Debug)]
+pub struct Multipart {
+    #[doc(hidden)]
+    parts: Vec<Part>,
+}
+
+impl Multipart {
+
+    /// Will parse a multipart/form-data body delimited by the boundary.
+    /// A malformed body returns a 400 Bad Request, a body or a part over the limits a 413 Payload Too Large.
+    pub fn parse(body: &[u8], boundary: &str, limits: &MultipartLimits) -> Result<Multipart, StatusCode> {
+        if body.len() > limits.max_total_size {
+            trace!("Multipart body of {} bytes is over the limit of {} bytes - Returning 413 Payload Too Large", body.len(), limits.max_total_size);
+            return Err(StatusCode::PayloadTooLarge);
+        }
+        if boundary.is_empty() || boundary.len() > 70 {
+            return Err(refuse("the boundary must be between 1 and 70 characters"));
+        }
+
+        let delimiter = format!("--{}", boundary).into_bytes();
+        let next_delimiter = format!("\r\n--{}", boundary).into_bytes();
+
+        // The preamble before the first delimiter is ignored. (RFC 2046 - 5.1.1)
+        let first = find(body, &delimiter).ok_or_else(|| refuse("no delimiter found"))?;
+        let mut rest = &body[first + delimiter.len()..];
+        let mut parts = Vec::new();
+
+        loop {
+            // The close delimiter ends the body, the epilogue is ignored.
+            if rest.starts_with(b"--") {
+                break;
+            }
+            rest = skip_transport_padding(rest).strip_prefix(b"\r\n").ok_or_else(|| refuse("a delimiter is not followed by CRLF"))?;
+
+            let end = find(rest, &next_delimiter).ok_or_else(|| refuse("a part is not terminated by a delimiter"))?;
+            let part = &rest[..end];
+            rest = &rest[end + next_delimiter.len()..];
+
+            // Part headers end with an empty line. A part without header starts directly with CRLF.
+            let (head, data) = match part.strip_prefix(b"\r\n") {
+                Some(data) => (&[][..], data),
+                None => {
+                    let pos = find(part, b"\r\n\r\n").ok_or_else(|| refuse("the headers of a part are not terminated"))?;
+                    (&part[..pos], &part[pos + 4..])
+                }
+            };
+
+            if data.len() > limits.max_part_size {
+                trace!("Multipart part of {} bytes is over the limit of {} bytes - Returning 413 Payload Too Large", data.len(), limits.max_part_size);
+                return Err(StatusCode::PayloadTooLarge);
+            }
+
+            parts.push(Part::new(parse_headers(head)?, data, limits)?);
+        }
+
+        trace!("Multipart body parsed - {} part(s) found.", parts.len());
+        Ok(Multipart { parts })
+    }
+
+    /// Will return the first part with this name, if present.
+    pub fn get(&self, name: &str) -> Option<&Part> {
+        self.parts.iter().find(|p| p.name.as_deref() == Some(name))
+    }
+
+    /// Will return every part with this name, in the order they were sent.
+    pub fn get_all(&self, name: &str) -> Vec<&Part> {
+        self.parts.iter().filter(|p| p.name.as_deref() == Some(name)).collect()
+    }
+
+    /// Will return only the parts that are files (with a filename).
+    pub fn files(&self) -> Vec<&Part> {
+        self.parts.iter().filter(|p| p.filename.is_some()).collect()
+    }
+
+    pub fn parts(&self) -> &[Part] {
+        &self.parts
+    }
+
+    pub fn into_parts(self) -> Vec<Part> {
+        self.parts
+    }
+
+    pub fn len(&self) -> usize {
+        self.parts.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.parts.is_empty()
+    }
+}
+
+
+/// One part of a multipart/form-data body: a form field or an uploaded file.
+#[derive(Debug)]
+pub struct Part {
+    /// Name of the form field, from the Content-Disposition header.
+    pub name: Option<String>,
+    /// Name of the uploaded file, from the Content-Disposition header.
+    pub filename: Option<String>,
+    /// Content-Type of the part. Per RFC 7578, it defaults to text/plain when not specified.
+    pub content_type: Option<String>,
+    /// Every header of the part.
+    pub headers: HeaderMap,
+    #[doc(hidden)]
+    data: PartData,
+}
+
+#[derive(Debug)]
+enum PartData {
+    Memory(Vec<u8>),
+    File(TempFile),
+}
+
+impl Part {
+
+    fn new(headers: HeaderMap, data: &[u8], limits: &MultipartLimits) -> Result<Part, StatusCode> {
+        let disposition = headers.get("Content-Disposition").unwrap_or_default();
+        let name = disposition_param(disposition, "name");
+        let filename = disposition_param(disposition, "filename");
+        let content_type = headers.content_type().map(String::from);
+
+        let data = if data.len() > limits.memory_threshold {
+            let file = TempFile::create(data).map_err(|e| {
+                warn!("Failed to write a multipart part to a temporary file - {}", e);
+                StatusCode::InternalServerError
+            })?;
+            PartData::File(file)
+        } else {
+            PartData::Memory(data.to_vec())
+        };
+
+        Ok(Part { name, filename, content_type, headers, data })
+    }
+
+    /// Size of the part content, in bytes.
+    pub fn size(&self) -> usize {
+        match &self.data {
+            PartData::Memory(data) => data.len(),
+            PartData::File(file) => file.size,
+        }
+    }
+
+    /// Will return true if the content has been written to a temporary file.
+    pub fn is_on_disk(&self) -> bool {
+        matches!(self.data, PartData::File(_))
+    }
+
+    /// Path of the temporary file holding the content, if it has been written on disk.
+    /// The file is removed when the Part is dropped: use `persist` to keep it.
+    pub fn path(&self) -> Option<&Path> {
+        match &self.data {
+            PartData::File(file) => Some(&file.path),
+            PartData::Memory(_) => None,
+        }
+    }
+
+    /// Will return the content of the part, reading the temporary file if needed.
+    pub fn bytes(&self) -> io::Result<Cow<'_, [u8]>> {
+        match &self.data {
+            PartData::Memory(data) => Ok(Cow::Borrowed(data)),
+            PartData::File(file) => fs::read(&file.path).map(Cow::Owned),
+        }
+    }
+
+    /// Will return the content of the part as text, invalid UTF-8 sequences being replaced by U+FFFD.
+    pub fn text(&self) -> io::Result<String> {
+        self.bytes().map(|b| String::from_utf8_lossy(&b).into_owned())
+    }
+
+    /// Will write the content of the part to the path provided.
+    pub fn persist<P: AsRef<Path>>(self, path: P) -> io::Result<()> {
+        match self.data {
+            PartData::Memory(data) => fs::write(path, data),
+            PartData::File(file) => {
+                // A rename fails across file systems, fall back to a copy.
+                if fs::rename(&file.path, path.as_ref()).is_err() {
+                    fs::copy(&file.path, path.as_ref())?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+
+// Temporary file removed when dropped.
+#[derive(Debug)]
+struct TempFile {
+    path: PathBuf,
+    size: usize,
+}
+
+impl TempFile {
+
+    fn create(data: &[u8]) -> io::Result<TempFile> {
+        static COUNTER: AtomicUsize = AtomicUsize::new(0);
+        let nanos = SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.subsec_nanos()).unwrap_or_default();
+        let path = std::env::temp_dir().join(format!("arkos-{}-{}-{}.part", std::process::id(), nanos, COUNTER.fetch_add(1, Ordering::Relaxed)));
+
+        let mut file = File::options().write(true).create_new(true).open(&path)?;
+        file.write_all(data)?;
+        trace!("Multipart part of {} bytes written to {}", data.len(), path.display());
+        Ok(TempFile { path, size: data.len() })
+    }
+}
+
+impl Drop for TempFile {
+    fn drop(&mut self) {
+        let _ = fs::remove_file(&self.path);
+    }
+}
+
+
+/// Will extract the boundary from a multipart/form-data Content-Type.
+/// Returns None if the Content-Type is not multipart/form-data or has no boundary.
+pub fn boundary(content_type: &str) -> Option<String> {
+    let mut params = content_type.split(';');
+    if !params.next()?.trim().eq_ignore_ascii_case("multipart/form-data") {
+        return None;
+    }
+    params.filter_map(|p| p.split_once('='))
+        .find(|(name, _)| name.trim().eq_ignore_ascii_case("boundary"))
+        .map(|(_, value)| unquote(value.trim()))
+}
+
+fn parse_headers(head: &[u8]) -> Result<HeaderMap, StatusCode> {
+    let mut headers = HeaderMap::new();
+    if head.is_empty() {
+        return Ok(headers);
+    }
+    for line in String::from_utf8_lossy(head).split("\r\n") {
+        let (name, value) = line.split_once(':').ok_or_else(|| refuse("a part header has no colon"))?;
+        headers.append(name.trim(), value.trim());
+    }
+    Ok(headers)
+}
+
+// Content-Disposition: form-data; name="file"; filename="cat.png"
+fn disposition_param(disposition: &str, param: &str) -> Option<String> {
+    disposition.split(';').skip(1)
+        .filter_map(|p| p.split_once('='))
+        .find(|(name, _)| name.trim().eq_ignore_ascii_case(param))
+        .map(|(_, value)| unquote(value.trim()))
+}
+
+fn unquote(value: &str) -> String {
+    match value.strip_prefix('"').and_then(|v| v.strip_suffix('"')) {
+        Some(inner) => inner.replace("\\\"", "\"").replace("\\\\", "\\"),
+        None => value.to_string(),
+    }
+}
+
+// Whitespace allowed after a delimiter. (RFC 2046 - 5.1.1)
+fn skip_transport_padding(data: &[u8]) -> &[u8] {
+    let start = data.iter().position(|b| *b != b' ' && *b != b'\t').unwrap_or(data.len());
+    &data[start..]
+}
+
+fn find(haystack: &[u8], needle: &[u8]) -> Option<usize> {
+    haystack.windows(needle.len()).position(|w| w == needle)
+}
+
+fn refuse(reason: &str) -> StatusCode {
+    trace!("Multipart body is malformed: {} - Returning 400 Bad Request", reason);
+    StatusCode::BadRequest
+}
+
+
+#[cfg(test)]
+mod test {
+
+    use super::*;
+
+    const BODY: &[u8] = b"preamble\r\n--XyZ\r\nContent-Disposition: form-data; name=\"title\"\r\n\r\nHoliday\r\n--XyZ\r\nContent-Disposition: form-data; name=\"picture\"; filename=\"cat \\\"1\\\".png\"\r\nContent-Type: image/png\r\n\r\n\x89PNG\r\n\x00\xff\r\n--XyZ--\r\nepilogue";
+
+    #[test]
+    fn boundary_extraction() {
+        assert_eq!(Some("XyZ".to_string()), boundary("multipart/form-data; boundary=XyZ"));
+        assert_eq!(Some("a b".to_string()), boundary("Multipart/Form-Data; charset=utf-8; boundary=\"a b\""));
+        assert_eq!(None, boundary("application/json"));
+    }
+
+    #[test]
+    fn fields_and_files() {
+        let multipart = Multipart::parse(BODY, "XyZ", &MultipartLimits::default()).unwrap();
+        assert_eq!(2, multipart.len());
+        assert_eq!("Holiday", multipart.get("title").unwrap().text().unwrap());
+
+        let picture = multipart.get("picture").unwrap();
+        assert_eq!(Some("cat \"1\".png".to_string()), picture.filename);
+        assert_eq!(Some("image/png".to_string()), picture.content_type);
+        assert_eq!(b"\x89PNG\r\n\x00\xff".to_vec(), picture.bytes().unwrap().into_owned());
+        assert_eq!(1, multipart.files().len());
+    }
+
+    #[test]
+    fn large_parts_are_written_to_disk() {
+        let limits = MultipartLimits { memory_threshold: 4, ..MultipartLimits::default() };
+        let multipart = Multipart::parse(BODY, "XyZ", &limits).unwrap();
+        let picture = multipart.get("picture").unwrap();
+        assert!(picture.is_on_disk());
+        let path = picture.path().unwrap().to_path_buf();
+        assert_eq!(b"\x89PNG\r\n\x00\xff".to_vec(), fs::read(&path).unwrap());
+
+        drop(multipart);
+        assert!(!path.exists());
+    }
+
+    #[test]
+    fn limits() {
+        let part_limit = MultipartLimits { max_part_size: 4, ..MultipartLimits::default() };
+        assert_eq!(StatusCode::PayloadTooLarge, Multipart::parse(BODY, "XyZ", &part_limit).unwrap_err());
+        let total_limit = MultipartLimits { max_total_size: 16, ..MultipartLimits::default() };
+        assert_eq!(StatusCode::PayloadTooLarge, Multipart::parse(BODY, "XyZ", &total_limit).unwrap_err());
+    }
+
+    #[test]
+    fn from_request() {
+        let head = format!("POST /upload HTTP/1.1\r\nHost: localhost\r\nContent-Type: multipart/form-data; boundary=XyZ\r\nContent-Length: {}\r\n\r\n", BODY.len());
+        let request = crate::server::request::Request::parse([head.as_bytes(), BODY].concat()).unwrap();
+        assert_eq!(2, request.multipart().unwrap().len());
+        assert_eq!(StatusCode::UnsupportedMediaType, request.form().unwrap_err());
+    }
+
+    #[test]
+    fn missing_close_delimiter() {
+        assert_eq!(StatusCode::BadRequest, Multipart::parse(b"--XyZ\r\nContent-Disposition: form-data; name=\"title\"\r\n\r\nHoliday", "XyZ", &MultipartLimits::default()).unwrap_err());
+        // A part may have no header at all. (RFC 2046 - 5.1.1)
+        let multipart = Multipart::parse(b"--XyZ\r\n\r\nplain\r\n--XyZ--", "XyZ", &MultipartLimits::default()).unwrap();
+        assert_eq!(1, multipart.len());
+        assert_eq!(b"plain".as_ref(), &*multipart.parts()[0].bytes().unwrap());
+        assert!(multipart.parts()[0].headers.is_empty() && multipart.parts()[0].name.is_none());
+    }
+
+}
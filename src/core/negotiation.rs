@@ -0,0 +1,194 @@
+use crate::core::content::ContentType;
+
+
+/// Member of an Accept, Accept-Language, Accept-Encoding or Accept-Charset header, with its weight.
+/// The weight is expressed in thousandths: `q=0.8` is 800, and a member without weight is 1000. (RFC 9110 - 12.4.2)
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct QualityItem {
+    pub value: String,
+    pub quality: u16,
+}
+
+/// Will parse the values of an Accept-* header into its members, the heaviest first.
+/// Members of equal weight keep the order of the header. Members with a malformed weight are ignored.
+/// The parameters of a member, but the weight, are kept in its value: `text/html;level=1`.
+pub fn parse_quality_list(values: &[&str]) -> Vec<QualityItem> {
+    let mut items: Vec<QualityItem> = values.iter().flat_map(|v| v.split(','))
+        .filter_map(|member| {
+            let mut params = member.split(';').map(str::trim);
+            let mut value = params.next().filter(|v| !v.is_empty())?.to_ascii_lowercase();
+            let mut quality = 1000;
+            for param in params {
+                match param.split_once('=') {
+                    Some((name, q)) if name.trim().eq_ignore_ascii_case("q") => quality = parse_quality(q.trim())?,
+                    _ if param.is_empty() => (),
+                    _ => {
+                        value.push(';');
+                        value.push_str(&param.to_ascii_lowercase());
+                    }
+                }
+            }
+            Some(QualityItem { value, quality })
+        })
+        .collect();
+    items.sort_by_key(|item| std::cmp::Reverse(item.quality));
+    items
+}
+
+// qvalue = ( "0" [ "." 0*3DIGIT ] ) / ( "1" [ "." 0*3("0") ] )
+fn parse_quality(q: &str) -> Option<u16> {
+    let (int, frac) = q.split_once('.').unwrap_or((q, ""));
+    if frac.len() > 3 || !frac.bytes().all(|b| b.is_ascii_digit()) {
+        return None;
+    }
+    let thousandths = format!("{:0<3}", frac).parse::<u16>().ok()?;
+    match int {
+        "0" => Some(thousandths),
+        "1" if thousandths == 0 => Some(1000),
+        _ => None,
+    }
+}
+
+
+/// Will return the Content-Type the client prefers among those offered, according to the Accept header.
+/// Without an Accept header, the first offered is returned. Returns None if none is acceptable.
+/// The most specific media range applies: `text/plain` over `text/*` over `*/*`.
+pub fn negotiate_content_type(accept: &[&str], offered: &[ContentType]) -> Option<ContentType> {
+    let items = parse_quality_list(accept);
+    if items.is_empty() {
+        return offered.first().cloned();
+    }
+    best(offered, |content_type| {
+        let essence = content_type.get().to_ascii_lowercase();
+        let (kind, _) = essence.split_once('/').unwrap_or((&essence, ""));
+        items.iter()
+            .filter_map(|item| {
+                let range = item.value.split(';').next().unwrap_or_default();
+                let specificity = match range.split_once('/') {
+                    _ if range == essence => 3,
+                    Some((k, "*")) if k == kind => 2,
+                    Some(("*", "*")) => 1,
+                    _ => return None,
+                };
+                Some((specificity, item.quality))
+            })
+            .max_by_key(|(specificity, _)| *specificity)
+            .map(|(_, quality)| quality)
+    })
+}
+
+/// Will return the language the client prefers among the tags offered, according to the Accept-Language header.
+/// A range matches a tag equal to it or starting with it followed by a `-`: `en` matches `en-US`. (RFC 4647 - 3.3.1)
+/// Without an Accept-Language header, the first offered is returned. Returns None if none is acceptable.
+pub fn negotiate_language(accept_language: &[&str], offered: &[&str]) -> Option<String> {
+    let items = parse_quality_list(accept_language);
+    if items.is_empty() {
+        return offered.first().map(|v| v.to_string());
+    }
+    best(offered, |tag| {
+        let tag = tag.to_ascii_lowercase();
+        items.iter()
+            .filter_map(|item| {
+                let specificity = match item.value.as_str() {
+                    "*" => 0,
+                    range if tag == range || (tag.starts_with(range) && tag.as_bytes()[range.len()] == b'-') => range.len(),
+                    _ => return None,
+                };
+                Some((specificity, item.quality))
+            })
+            .max_by_key(|(specificity, _)| *specificity)
+            .map(|(_, quality)| quality)
+    }).map(|v| v.to_string())
+}
+
+/// Will return the content coding the client prefers among those offered, according to the Accept-Encoding header.
+/// `identity` is acceptable unless explicitly refused with `identity;q=0` or `*;q=0`. (RFC 9110 - 12.5.3)
+/// Without an Accept-Encoding header, the first offered is returned. Returns None if none is acceptable.
+pub fn negotiate_encoding(accept_encoding: &[&str], offered: &[&str]) -> Option<String> {
+    let items = parse_quality_list(accept_encoding);
+    if items.is_empty() {
+        return offered.first().map(|v| v.to_string());
+    }
+    best(offered, |coding| {
+        let coding = coding.to_ascii_lowercase();
+        exact_or_wildcard(&items, &coding).or(match coding.as_str() {
+            "identity" => Some(1),
+            _ => None,
+        })
+    }).map(|v| v.to_string())
+}
+
+/// Will return the charset the client prefers among those offered, according to the Accept-Charset header.
+/// Without an Accept-Charset header, the first offered is returned. Returns None if none is acceptable.
+pub fn negotiate_charset(accept_charset: &[&str], offered: &[&str]) -> Option<String> {
+    let items = parse_quality_list(accept_charset);
+    if items.is_empty() {
+        return offered.first().map(|v| v.to_string());
+    }
+    best(offered, |charset| exact_or_wildcard(&items, &charset.to_ascii_lowercase())).map(|v| v.to_string())
+}
+
+// Weight of the member naming the value, or else of the wildcard.
+fn exact_or_wildcard(items: &[QualityItem], value: &str) -> Option<u16> {
+    items.iter().find(|item| item.value == value)
+        .or_else(|| items.iter().find(|item| item.value == "*"))
+        .map(|item| item.quality)
+}
+
+// Heaviest acceptable value offered. On equal weight, the first offered wins.
+fn best<T: Clone>(offered: &[T], quality: impl Fn(&T) -> Option<u16>) -> Option<T> {
+    let mut best: Option<(&T, u16)> = None;
+    for value in offered {
+        match quality(value) {
+            Some(q) if q > 0 && !matches!(best, Some((_, b)) if q <= b) => best = Some((value, q)),
+            _ => (),
+        }
+    }
+    best.map(|(value, _)| value.clone())
+}
+
+
+#[cfg(test)]
+mod test {
+
+    use super::*;
+
+    #[test]
+    fn quality_list() {
+        let items = parse_quality_list(&["text/html;q=0.5, application/json", "text/plain;level=1;q=0.8, text/*;q=1.5"]);
+        assert_eq!(vec![
+            QualityItem { value: "application/json".into(), quality: 1000 },
+            QualityItem { value: "text/plain;level=1".into(), quality: 800 },
+            QualityItem { value: "text/html".into(), quality: 500 },
+        ], items);
+    }
+
+    #[test]
+    fn content_type() {
+        let offered = [ContentType::Json, ContentType::Text];
+        assert_eq!(Some(ContentType::Json), negotiate_content_type(&[], &offered));
+        assert_eq!(Some(ContentType::Text), negotiate_content_type(&["text/plain, application/json;q=0.9"], &offered));
+        assert_eq!(Some(ContentType::Text), negotiate_content_type(&["text/*, */*;q=0.1"], &offered));
+        assert_eq!(Some(ContentType::Json), negotiate_content_type(&["*/*"], &offered));
+        assert_eq!(None, negotiate_content_type(&["text/html"], &offered));
+        // The most specific range applies, even with a lower weight.
+        assert_eq!(None, negotiate_content_type(&["text/*, text/plain;q=0"], &[ContentType::Text]));
+    }
+
+    #[test]
+    fn language() {
+        let offered = ["en-US", "fr-FR"];
+        assert_eq!(Some("fr-FR".to_string()), negotiate_language(&["fr, en;q=0.8"], &offered));
+        assert_eq!(Some("en-US".to_string()), negotiate_language(&["de, *;q=0.5"], &offered));
+        assert_eq!(None, negotiate_language(&["fr-CA, e"], &offered));
+    }
+
+    #[test]
+    fn encoding_and_charset() {
+        assert_eq!(Some("br".to_string()), negotiate_encoding(&["gzip;q=0.5, br"], &["gzip", "br", "identity"]));
+        assert_eq!(Some("identity".to_string()), negotiate_encoding(&["deflate"], &["gzip", "identity"]));
+        assert_eq!(None, negotiate_encoding(&["*;q=0"], &["gzip", "identity"]));
+        assert_eq!(Some("utf-8".to_string()), negotiate_charset(&["iso-8859-1;q=0.5, UTF-8"], &["iso-8859-1", "utf-8"]));
+    }
+
+}
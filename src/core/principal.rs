@@ -0,0 +1,34 @@
+/// Authenticated user or client, attached to the Request by an authentication step: `request.insert_extension(principal)`.
+/// Routes declaring roles or scopes are evaluated against it.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Principal {
+    pub name: String,
+    pub roles: Vec<String>,
+    pub scopes: Vec<String>,
+}
+
+impl Principal {
+
+    /// Will create a Principal without any role or scope.
+    pub fn new(name: &str) -> Self {
+        Principal { name: name.into(), roles: Vec::new(), scopes: Vec::new() }
+    }
+
+    pub fn with_roles(mut self, roles: &[&str]) -> Self {
+        self.roles.extend(roles.iter().map(|r| r.to_string()));
+        self
+    }
+
+    pub fn with_scopes(mut self, scopes: &[&str]) -> Self {
+        self.scopes.extend(scopes.iter().map(|s| s.to_string()));
+        self
+    }
+
+    pub fn has_role(&self, role: &str) -> bool {
+        self.roles.iter().any(|r| r == role)
+    }
+
+    pub fn has_scope(&self, scope: &str) -> bool {
+        self.scopes.iter().any(|s| s == scope)
+    }
+}
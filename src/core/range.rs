@@ -0,0 +1,101 @@
+use std::ops::Range;
+
+
+/// Range of bytes asked in the Range header of a Request. (RFC 9110 - 14.1.2)
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ByteRangeSpec {
+    /// `first-last`, both included.
+    FromTo(u64, u64),
+    /// `first-`: from the byte to the end.
+    From(u64),
+    /// `-length`: the last bytes.
+    Suffix(u64),
+}
+
+impl ByteRangeSpec {
+
+    /// Will parse the value of a Range header. Returns None if the unit is not `bytes` or if the header is malformed,
+    /// the header being then ignored.
+    pub fn parse(value: &str) -> Option<Vec<ByteRangeSpec>> {
+        let (unit, specs) = value.trim().split_once('=')?;
+        if !unit.trim().eq_ignore_ascii_case("bytes") {
+            return None;
+        }
+        let number = |n: &str| if !n.is_empty() && n.bytes().all(|b| b.is_ascii_digit()) { n.parse::<u64>().ok() } else { None };
+        let specs = specs.split(',').map(str::trim).filter(|s| !s.is_empty())
+            .map(|spec| match spec.split_once('-')? {
+                ("", length) => Some(ByteRangeSpec::Suffix(number(length)?)),
+                (first, "") => Some(ByteRangeSpec::From(number(first)?)),
+                (first, last) => {
+                    let (first, last) = (number(first)?, number(last)?);
+                    (first <= last).then_some(ByteRangeSpec::FromTo(first, last))
+                }
+            })
+            .collect::<Option<Vec<_>>>()?;
+        (!specs.is_empty()).then_some(specs)
+    }
+
+    /// Will return the range of bytes of a representation of this length, if satisfiable.
+    pub fn resolve(&self, length: u64) -> Option<Range<u64>> {
+        let range = match *self {
+            ByteRangeSpec::FromTo(first, last) => first..last.saturating_add(1).min(length),
+            ByteRangeSpec::From(first) => first..length,
+            ByteRangeSpec::Suffix(suffix) => length.saturating_sub(suffix)..length,
+        };
+        (range.start < range.end).then_some(range)
+    }
+}
+
+/// Will return the satisfiable ranges of a representation of this length, sorted, overlapping and adjacent ranges being merged.
+/// An empty list means the Range is not satisfiable.
+pub fn resolve_ranges(specs: &[ByteRangeSpec], length: u64) -> Vec<Range<u64>> {
+    let mut ranges: Vec<Range<u64>> = specs.iter().filter_map(|s| s.resolve(length)).collect();
+    ranges.sort_by_key(|r| r.start);
+    let mut merged: Vec<Range<u64>> = Vec::with_capacity(ranges.len());
+    for range in ranges {
+        match merged.last_mut() {
+            Some(last) if range.start <= last.end => last.end = last.end.max(range.end),
+            _ => merged.push(range),
+        }
+    }
+    merged
+}
+
+/// Value of the Content-Range header of a part: `bytes 0-499/1234`.
+pub fn content_range(range: &Range<u64>, length: u64) -> String {
+    format!("bytes {}-{}/{}", range.start, range.end - 1, length)
+}
+
+
+#[cfg(test)]
+mod test {
+
+    use super::*;
+
+    #[test]
+    fn parse() {
+        assert_eq!(Some(vec![ByteRangeSpec::FromTo(0, 499), ByteRangeSpec::From(9500), ByteRangeSpec::Suffix(500)]), ByteRangeSpec::parse("bytes=0-499, 9500-,-500"));
+        assert_eq!(None, ByteRangeSpec::parse("items=0-4"));
+        assert_eq!(None, ByteRangeSpec::parse("bytes=5-4"));
+        assert_eq!(None, ByteRangeSpec::parse("bytes=-"));
+        assert_eq!(None, ByteRangeSpec::parse("bytes=a-4"));
+    }
+
+    #[test]
+    fn resolve() {
+        assert_eq!(Some(0..500), ByteRangeSpec::FromTo(0, 499).resolve(10000));
+        assert_eq!(Some(9500..10000), ByteRangeSpec::FromTo(9500, 20000).resolve(10000));
+        assert_eq!(Some(0..100), ByteRangeSpec::Suffix(500).resolve(100));
+        assert_eq!(None, ByteRangeSpec::From(100).resolve(100));
+        assert_eq!(None, ByteRangeSpec::Suffix(0).resolve(100));
+    }
+
+    #[test]
+    fn merge() {
+        let specs = ByteRangeSpec::parse("bytes=50-59,0-9,5-19,20-29,-10").unwrap();
+        assert_eq!(vec![0..30, 50..60, 90..100], resolve_ranges(&specs, 100));
+        assert_eq!(Vec::<Range<u64>>::new(), resolve_ranges(&specs, 0));
+        assert_eq!("bytes 0-29/100", content_range(&(0..30), 100));
+    }
+
+}
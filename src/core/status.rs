@@ -1,3 +1,10 @@
+use std::convert::TryFrom;
+use std::fmt;
+
+use log::warn;
+
+use super::{date::HttpDate, method::HttpMethod};
+
 pub trait HttpStatusCode {
     fn get_code(&self) -> u16;
     fn get_title(&self) -> String;
@@ -11,8 +18,8 @@ pub enum StatusCode {
     Continue, SwitchingProtocols(String), Processing, EarlyHints,
     Ok, Created, Accepted, NonAuthoritativeInformations, NoContent, ResetContent, PartialContent, MultiStatus, AlreadyReported, ContentDifferent, IMUsed,
     MultipleChoices, MovedPermanently(String), Found, SeeOther, NotModified, UseProxy, SwitchProxy, TemporaryRedirect(String), PermanentRedirect(String),
-    BadRequest, Unauthorized, PaymentRequired, Forbidden, NotFound, MethodNotAllowed, NotAcceptable, ProxyAuthenticationRequired, RequestTimeout, Conflict, Gone, LengthRequired, PreconditionFailed, PayloadTooLarge, URIToolLong, UnsupportedMediaType, RangeNotSatisfiable, ExpectationFailed, ImATeaPot, MisdirectionRequest, UnprocessableEntity, Locked, FailedDependency, TooEarly, UpgradeRequired(String), PreconditionRequired, TooManyRequests(String), RequestHeaderFieldsTooLarge, UnavailableForLegalReasons,
-    InternalServerError, NotImplemented, BadGateway, ServiceUnavailable(String), GatewayTimeout, HTTPVersionNotSupported, VariantAlsoNegotiates, InsufficientStorage, LoopDetected, NotExtended, NetworkAuthenticationRequired,
+    BadRequest, Unauthorized(String), PaymentRequired, Forbidden, NotFound, MethodNotAllowed(Vec<HttpMethod>), NotAcceptable, ProxyAuthenticationRequired(String), RequestTimeout, Conflict, Gone, LengthRequired, PreconditionFailed, PayloadTooLarge, URIToolLong, UnsupportedMediaType, RangeNotSatisfiable(Option<u64>), ExpectationFailed, ImATeaPot, MisdirectionRequest, UnprocessableEntity, Locked, FailedDependency, TooEarly, UpgradeRequired(String), PreconditionRequired, TooManyRequests(Option<RetryAfter>), RequestHeaderFieldsTooLarge, UnavailableForLegalReasons,
+    InternalServerError, NotImplemented, BadGateway, ServiceUnavailable(Option<RetryAfter>), GatewayTimeout, HTTPVersionNotSupported, VariantAlsoNegotiates, InsufficientStorage, LoopDetected, NotExtended, NetworkAuthenticationRequired,
     Custom(u16, String),
 }
 
@@ -45,13 +52,13 @@ impl HttpStatusCode for StatusCode {
             StatusCode::TemporaryRedirect(_) => 307, 
             StatusCode::PermanentRedirect(_) => 308,
             StatusCode::BadRequest => 400, 
-            StatusCode::Unauthorized => 401, 
+            StatusCode::Unauthorized(_) => 401, 
             StatusCode::PaymentRequired => 402, 
             StatusCode::Forbidden => 403, 
             StatusCode::NotFound => 404, 
-            StatusCode::MethodNotAllowed => 405, 
+            StatusCode::MethodNotAllowed(_) => 405, 
             StatusCode::NotAcceptable => 406, 
-            StatusCode::ProxyAuthenticationRequired => 407, 
+            StatusCode::ProxyAuthenticationRequired(_) => 407, 
             StatusCode::RequestTimeout => 408, 
             StatusCode::Conflict => 409, 
             StatusCode::Gone => 410, 
@@ -60,7 +67,7 @@ impl HttpStatusCode for StatusCode {
             StatusCode::PayloadTooLarge => 413, 
             StatusCode::URIToolLong => 414, 
             StatusCode::UnsupportedMediaType => 415, 
-            StatusCode::RangeNotSatisfiable => 416, 
+            StatusCode::RangeNotSatisfiable(_) => 416, 
             StatusCode::ExpectationFailed => 417, 
             StatusCode::ImATeaPot => 418, 
             StatusCode::MisdirectionRequest => 421, 
@@ -114,14 +121,14 @@ impl HttpStatusCode for StatusCode {
             StatusCode::SwitchProxy => "Switch Proxy", 
             StatusCode::TemporaryRedirect(_) => "Temporary Redirect", 
             StatusCode::PermanentRedirect(_) => "Permanent Redirect",
-            StatusCode::BadRequest => "Bad Redirect", 
-            StatusCode::Unauthorized => "Unauthorized", 
+            StatusCode::BadRequest => "Bad Request", 
+            StatusCode::Unauthorized(_) => "Unauthorized", 
             StatusCode::PaymentRequired => "Payment Required", 
             StatusCode::Forbidden => "Forbidden", 
             StatusCode::NotFound => "Not Found", 
-            StatusCode::MethodNotAllowed => "Method Not Allowed", 
+            StatusCode::MethodNotAllowed(_) => "Method Not Allowed", 
             StatusCode::NotAcceptable => "Not Acceptable", 
-            StatusCode::ProxyAuthenticationRequired => "Proxy Authentication Required", 
+            StatusCode::ProxyAuthenticationRequired(_) => "Proxy Authentication Required", 
             StatusCode::RequestTimeout => "Request Timeout", 
             StatusCode::Conflict => "Conflict", 
             StatusCode::Gone => "Gone", 
@@ -130,7 +137,7 @@ impl HttpStatusCode for StatusCode {
             StatusCode::PayloadTooLarge => "Payload Too Large", 
             StatusCode::URIToolLong => "URI Too Long", 
             StatusCode::UnsupportedMediaType => "Unsupported Media Type", 
-            StatusCode::RangeNotSatisfiable => "Range Not Satisfiable", 
+            StatusCode::RangeNotSatisfiable(_) => "Range Not Satisfiable", 
             StatusCode::ExpectationFailed => "Expectation Failed", 
             StatusCode::ImATeaPot => "I'm A Tea Pot", 
             StatusCode::MisdirectionRequest => "Misdirection Request", 
@@ -161,9 +168,13 @@ impl HttpStatusCode for StatusCode {
 
     fn generate_headers(&self) -> String {
         match self {
-            StatusCode::SwitchingProtocols(u) | StatusCode::UpgradeRequired(u) => format!("Upgrade: {}\r\n",u),
-            StatusCode::MovedPermanently(l) | StatusCode::PermanentRedirect(l) | StatusCode::TemporaryRedirect(l) => format!("Location: {}\r\n", l),
-            StatusCode::TooManyRequests(d) | StatusCode::ServiceUnavailable(d) => format!("Location: {}\r\n", d),
+            StatusCode::SwitchingProtocols(u) | StatusCode::UpgradeRequired(u) => header("Upgrade", u),
+            StatusCode::MovedPermanently(l) | StatusCode::PermanentRedirect(l) | StatusCode::TemporaryRedirect(l) => header("Location", l),
+            StatusCode::TooManyRequests(Some(d)) | StatusCode::ServiceUnavailable(Some(d)) => header("Retry-After", &d.to_string()),
+            StatusCode::Unauthorized(c) => header("WWW-Authenticate", c),
+            StatusCode::ProxyAuthenticationRequired(c) => header("Proxy-Authenticate", c),
+            StatusCode::MethodNotAllowed(m) => header("Allow", &m.iter().map(HttpMethod::to_string).collect::<Vec<String>>().join(", ")),
+            StatusCode::RangeNotSatisfiable(Some(length)) => header("Content-Range", &format!("bytes */{}", length)),
             _ => "".into(),
         }
     }
@@ -171,75 +182,287 @@ impl HttpStatusCode for StatusCode {
 
 }
 
+// Header line carried by a status. An empty value is not sent, and a value breaking the line is refused.
+fn header(name: &str, value: &str) -> String {
+    if value.is_empty() {
+        return "".into();
+    }
+    if value.contains(['\r', '\n', '\0']) {
+        warn!("The {} header of the status contains a line break - It is not sent.", name);
+        return "".into();
+    }
+    format!("{}: {}\r\n", name, value)
+}
+
+
+/// Delay of a Retry-After header, sent with a 429 Too Many Requests or a 503 Service Unavailable:
+/// `StatusCode::TooManyRequests(Some(RetryAfter::Seconds(120)))`. (RFC 9110 - 10.2.3)
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum RetryAfter {
+    Seconds(u64),
+    Date(HttpDate),
+}
+
+impl RetryAfter {
+
+    /// Will parse a number of seconds or an HTTP-date. Returns None if the value is malformed.
+    pub fn parse(value: &str) -> Option<RetryAfter> {
+        let value = value.trim();
+        match value.bytes().all(|b| b.is_ascii_digit()) {
+            true => value.parse().ok().map(RetryAfter::Seconds),
+            false => HttpDate::parse(value).map(RetryAfter::Date),
+        }
+    }
+}
+
+impl fmt::Display for RetryAfter {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RetryAfter::Seconds(secs) => write!(f, "{}", secs),
+            RetryAfter::Date(date) => write!(f, "{}", date),
+        }
+    }
+}
+
+
 impl StatusCode {
 
+    /// Will create a status code unknown to Arkos, as `StatusCode::custom(299, "Cached")`.
+    /// Returns an error if the code is not within 100 and 999. (RFC 9110 - 15)
+    pub fn custom(code: u16, title: &str) -> Result<StatusCode, InvalidStatusCode> {
+        match (100..=999).contains(&code) {
+            true => Ok(StatusCode::Custom(code, title.into())),
+            false => Err(InvalidStatusCode(code)),
+        }
+    }
+
+    /// Will parse a status code as "404". A known code returns its variant, with an empty header payload, and an unknown one a Custom status.
+    /// A malformed code returns a 500 Internal Server Error.
     pub fn from_str(val: &str) -> StatusCode {
-        match val {
-            "100" => StatusCode::Ok, 
-            "101" => StatusCode::SwitchingProtocols("".into()), 
-            "102" => StatusCode::Processing , 
-            "103" => StatusCode::EarlyHints,
-            "200" => StatusCode::Ok , 
-            "201" => StatusCode::Created  , 
-            "202" => StatusCode::Accepted  , 
-            "203" => StatusCode::NonAuthoritativeInformations  , 
-            "204" => StatusCode::NoContent  , 
-            "205" => StatusCode::ResetContent  , 
-            "206" => StatusCode::PartialContent  , 
-            "207" => StatusCode::MultiStatus  , 
-            "208" => StatusCode::AlreadyReported  , 
-            "210" => StatusCode::ContentDifferent  , 
-            "226" => StatusCode::IMUsed,
-            "300" => StatusCode::MultipleChoices , 
-            "301" => StatusCode::MovedPermanently("".into()) , 
-            "302" => StatusCode::Found  , 
-            "303" => StatusCode::SeeOther  , 
-            "304" => StatusCode::NotModified  , 
-            "305" => StatusCode::UseProxy  , 
-            "306" => StatusCode::SwitchProxy  , 
-            "307" => StatusCode::TemporaryRedirect("".into())  , 
-            "308" => StatusCode::PermanentRedirect("".into()),
-            "400" => StatusCode::BadRequest  , 
-            "401" => StatusCode::Unauthorized  , 
-            "402" => StatusCode::PaymentRequired  , 
-            "403" => StatusCode::Forbidden  , 
-            "404" => StatusCode::NotFound  , 
-            "405" => StatusCode::MethodNotAllowed  , 
-            "406" => StatusCode::NotAcceptable  , 
-            "407" => StatusCode::ProxyAuthenticationRequired  , 
-            "408" => StatusCode::RequestTimeout  , 
-            "409" => StatusCode::Conflict  , 
-            "410" => StatusCode::Gone  , 
-            "411" => StatusCode::LengthRequired  , 
-            "412" => StatusCode::PreconditionFailed  , 
-            "413" => StatusCode::PayloadTooLarge  , 
-            "414" => StatusCode::URIToolLong  , 
-            "415" => StatusCode::UnsupportedMediaType  , 
-            "416" => StatusCode::RangeNotSatisfiable  , 
-            "417" => StatusCode::ExpectationFailed  , 
-            "418" => StatusCode::ImATeaPot  , 
-            "421" => StatusCode::MisdirectionRequest  , 
-            "422" => StatusCode::UnprocessableEntity  , 
-            "423" => StatusCode::Locked  , 
-            "424" => StatusCode::FailedDependency  , 
-            "425" => StatusCode::TooEarly  , 
-            "426" => StatusCode::UpgradeRequired("".into())  , 
-            "428" => StatusCode::PreconditionRequired  , 
-            "429" => StatusCode::TooManyRequests("".into())  , 
-            "431" => StatusCode::RequestHeaderFieldsTooLarge  , 
-            "451" => StatusCode::UnavailableForLegalReasons,
-            "501" => StatusCode::InternalServerError  , 
-            "502" => StatusCode::NotImplemented  , 
-            "503" => StatusCode::BadGateway  , 
-            "504" => StatusCode::ServiceUnavailable("".into())  , 
-            "505" => StatusCode::GatewayTimeout  , 
-            "506" => StatusCode::HTTPVersionNotSupported  , 
-            "507" => StatusCode::VariantAlsoNegotiates  , 
-            "508" => StatusCode::InsufficientStorage  , 
-            "509" => StatusCode::LoopDetected  , 
-            "510" => StatusCode::NotExtended  , 
-            "511" => StatusCode::NetworkAuthenticationRequired  ,
-            _ => StatusCode::InternalServerError,
+        val.trim().parse::<u16>().ok().and_then(|code| StatusCode::try_from(code).ok()).unwrap_or(StatusCode::InternalServerError)
+    }
+
+    /// 1xx: the Request was received, the processing continues.
+    pub fn is_informational(&self) -> bool {
+        (100..200).contains(&self.get_code())
+    }
+
+    /// 2xx: the Request was accepted.
+    pub fn is_success(&self) -> bool {
+        (200..300).contains(&self.get_code())
+    }
+
+    /// 3xx: the client has to take further action.
+    pub fn is_redirection(&self) -> bool {
+        (300..400).contains(&self.get_code())
+    }
+
+    /// 4xx: the Request is wrong.
+    pub fn is_client_error(&self) -> bool {
+        (400..500).contains(&self.get_code())
+    }
+
+    /// 5xx: the server failed to handle a valid Request.
+    pub fn is_server_error(&self) -> bool {
+        (500..600).contains(&self.get_code())
+    }
+}
+
+impl TryFrom<u16> for StatusCode {
+    type Error = InvalidStatusCode;
+
+    /// Will return the variant of the code, with an empty header payload. An unknown code within 100 and 999 is a Custom status without title.
+    fn try_from(code: u16) -> Result<Self, Self::Error> {
+        let status = match code {
+            100 => StatusCode::Continue,
+            101 => StatusCode::SwitchingProtocols("".into()),
+            102 => StatusCode::Processing,
+            103 => StatusCode::EarlyHints,
+            200 => StatusCode::Ok,
+            201 => StatusCode::Created,
+            202 => StatusCode::Accepted,
+            203 => StatusCode::NonAuthoritativeInformations,
+            204 => StatusCode::NoContent,
+            205 => StatusCode::ResetContent,
+            206 => StatusCode::PartialContent,
+            207 => StatusCode::MultiStatus,
+            208 => StatusCode::AlreadyReported,
+            210 => StatusCode::ContentDifferent,
+            226 => StatusCode::IMUsed,
+            300 => StatusCode::MultipleChoices,
+            301 => StatusCode::MovedPermanently("".into()),
+            302 => StatusCode::Found,
+            303 => StatusCode::SeeOther,
+            304 => StatusCode::NotModified,
+            305 => StatusCode::UseProxy,
+            306 => StatusCode::SwitchProxy,
+            307 => StatusCode::TemporaryRedirect("".into()),
+            308 => StatusCode::PermanentRedirect("".into()),
+            400 => StatusCode::BadRequest,
+            401 => StatusCode::Unauthorized("".into()),
+            402 => StatusCode::PaymentRequired,
+            403 => StatusCode::Forbidden,
+            404 => StatusCode::NotFound,
+            405 => StatusCode::MethodNotAllowed(Vec::new()),
+            406 => StatusCode::NotAcceptable,
+            407 => StatusCode::ProxyAuthenticationRequired("".into()),
+            408 => StatusCode::RequestTimeout,
+            409 => StatusCode::Conflict,
+            410 => StatusCode::Gone,
+            411 => StatusCode::LengthRequired,
+            412 => StatusCode::PreconditionFailed,
+            413 => StatusCode::PayloadTooLarge,
+            414 => StatusCode::URIToolLong,
+            415 => StatusCode::UnsupportedMediaType,
+            416 => StatusCode::RangeNotSatisfiable(None),
+            417 => StatusCode::ExpectationFailed,
+            418 => StatusCode::ImATeaPot,
+            421 => StatusCode::MisdirectionRequest,
+            422 => StatusCode::UnprocessableEntity,
+            423 => StatusCode::Locked,
+            424 => StatusCode::FailedDependency,
+            425 => StatusCode::TooEarly,
+            426 => StatusCode::UpgradeRequired("".into()),
+            428 => StatusCode::PreconditionRequired,
+            429 => StatusCode::TooManyRequests(None),
+            431 => StatusCode::RequestHeaderFieldsTooLarge,
+            451 => StatusCode::UnavailableForLegalReasons,
+            500 => StatusCode::InternalServerError,
+            501 => StatusCode::NotImplemented,
+            502 => StatusCode::BadGateway,
+            503 => StatusCode::ServiceUnavailable(None),
+            504 => StatusCode::GatewayTimeout,
+            505 => StatusCode::HTTPVersionNotSupported,
+            506 => StatusCode::VariantAlsoNegotiates,
+            507 => StatusCode::InsufficientStorage,
+            508 => StatusCode::LoopDetected,
+            510 => StatusCode::NotExtended,
+            511 => StatusCode::NetworkAuthenticationRequired,
+            _ => return StatusCode::custom(code, ""),
+        };
+        Ok(status)
+    }
+}
+
+impl From<&StatusCode> for u16 {
+    fn from(status: &StatusCode) -> u16 {
+        status.get_code()
+    }
+}
+
+impl From<StatusCode> for u16 {
+    fn from(status: StatusCode) -> u16 {
+        status.get_code()
+    }
+}
+
+/// Displayed as the status line: `404 Not Found`.
+impl fmt::Display for StatusCode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.get_title().as_str() {
+            "" => write!(f, "{}", self.get_code()),
+            title => write!(f, "{} {}", self.get_code(), title),
+        }
+    }
+}
+
+
+/// Status code outside of 100 and 999.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct InvalidStatusCode(pub u16);
+
+impl fmt::Display for InvalidStatusCode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} is not a status code: it must be within 100 and 999", self.0)
+    }
+}
+
+impl std::error::Error for InvalidStatusCode {}
+
+
+#[cfg(test)]
+mod test {
+
+    use super::*;
+
+    #[test]
+    fn retry_after() {
+        assert_eq!("Retry-After: 120\r\n", StatusCode::TooManyRequests(Some(RetryAfter::Seconds(120))).generate_headers());
+        let date = RetryAfter::Date(HttpDate::from_unix(784111777));
+        assert_eq!("Retry-After: Sun, 06 Nov 1994 08:49:37 GMT\r\n", StatusCode::ServiceUnavailable(Some(date)).generate_headers());
+        assert_eq!("", StatusCode::ServiceUnavailable(None).generate_headers());
+        assert_eq!(Some(date), RetryAfter::parse("Sun, 06 Nov 1994 08:49:37 GMT"));
+        assert_eq!(Some(RetryAfter::Seconds(0)), RetryAfter::parse(" 0 "));
+        assert_eq!(None, RetryAfter::parse("-1"));
+        assert_eq!(None, RetryAfter::parse(""));
+    }
+
+    #[test]
+    fn status_headers() {
+        assert_eq!("Allow: GET, HEAD\r\n", StatusCode::MethodNotAllowed(vec![HttpMethod::GET, HttpMethod::HEAD]).generate_headers());
+        assert_eq!("", StatusCode::MethodNotAllowed(Vec::new()).generate_headers());
+        assert_eq!("Content-Range: bytes */1024\r\n", StatusCode::RangeNotSatisfiable(Some(1024)).generate_headers());
+        assert_eq!("Proxy-Authenticate: Basic realm=\"proxy\"\r\n", StatusCode::ProxyAuthenticationRequired("Basic realm=\"proxy\"".into()).generate_headers());
+        assert_eq!("WWW-Authenticate: Bearer\r\n", StatusCode::Unauthorized("Bearer".into()).generate_headers());
+        assert_eq!("", StatusCode::MovedPermanently("/new\r\nSet-Cookie: a=b".into()).generate_headers());
+    }
+
+    fn every_status() -> Vec<StatusCode> {
+        vec![
+            StatusCode::Continue, StatusCode::SwitchingProtocols("".into()), StatusCode::Processing, StatusCode::EarlyHints,
+            StatusCode::Ok, StatusCode::Created, StatusCode::Accepted, StatusCode::NonAuthoritativeInformations, StatusCode::NoContent, StatusCode::ResetContent, StatusCode::PartialContent, StatusCode::MultiStatus, StatusCode::AlreadyReported, StatusCode::ContentDifferent, StatusCode::IMUsed,
+            StatusCode::MultipleChoices, StatusCode::MovedPermanently("".into()), StatusCode::Found, StatusCode::SeeOther, StatusCode::NotModified, StatusCode::UseProxy, StatusCode::SwitchProxy, StatusCode::TemporaryRedirect("".into()), StatusCode::PermanentRedirect("".into()),
+            StatusCode::BadRequest, StatusCode::Unauthorized("".into()), StatusCode::PaymentRequired, StatusCode::Forbidden, StatusCode::NotFound, StatusCode::MethodNotAllowed(Vec::new()), StatusCode::NotAcceptable, StatusCode::ProxyAuthenticationRequired("".into()), StatusCode::RequestTimeout, StatusCode::Conflict, StatusCode::Gone, StatusCode::LengthRequired, StatusCode::PreconditionFailed, StatusCode::PayloadTooLarge, StatusCode::URIToolLong, StatusCode::UnsupportedMediaType, StatusCode::RangeNotSatisfiable(None), StatusCode::ExpectationFailed, StatusCode::ImATeaPot, StatusCode::MisdirectionRequest, StatusCode::UnprocessableEntity, StatusCode::Locked, StatusCode::FailedDependency, StatusCode::TooEarly, StatusCode::UpgradeRequired("".into()), StatusCode::PreconditionRequired, StatusCode::TooManyRequests(None), StatusCode::RequestHeaderFieldsTooLarge, StatusCode::UnavailableForLegalReasons,
+            StatusCode::InternalServerError, StatusCode::NotImplemented, StatusCode::BadGateway, StatusCode::ServiceUnavailable(None), StatusCode::GatewayTimeout, StatusCode::HTTPVersionNotSupported, StatusCode::VariantAlsoNegotiates, StatusCode::InsufficientStorage, StatusCode::LoopDetected, StatusCode::NotExtended, StatusCode::NetworkAuthenticationRequired,
+        ]
+    }
+
+    #[test]
+    fn every_variant_round_trips() {
+        let statuses = every_status();
+        for status in &statuses {
+            assert_eq!(Ok(status.clone()), StatusCode::try_from(status.get_code()));
+            assert_eq!(*status, StatusCode::from_str(&status.get_code().to_string()));
         }
+        // Every code not listed is a Custom status.
+        let known = (100..=999u16).filter(|c| !matches!(StatusCode::try_from(*c), Ok(StatusCode::Custom(_, _)))).count();
+        assert_eq!(statuses.len(), known);
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn every_code_round_trips() {
+        for code in 100..=999u16 {
+            let status = StatusCode::try_from(code).unwrap();
+            assert_eq!(code, u16::from(&status));
+            assert_eq!(code, status.to_string()[..3].parse::<u16>().unwrap());
+        }
+        assert_eq!(Err(InvalidStatusCode(99)), StatusCode::try_from(99));
+        assert_eq!(Err(InvalidStatusCode(1000)), StatusCode::try_from(1000));
+        assert_eq!(Err(InvalidStatusCode(0)), StatusCode::custom(0, "Zero"));
+        assert_eq!(Ok(StatusCode::Custom(299, "Cached".into())), StatusCode::custom(299, "Cached"));
+    }
+
+    #[test]
+    fn from_str_codes() {
+        assert_eq!(StatusCode::Continue, StatusCode::from_str("100"));
+        assert_eq!(StatusCode::NotImplemented, StatusCode::from_str("501"));
+        assert_eq!(StatusCode::ServiceUnavailable(None), StatusCode::from_str("503"));
+        assert_eq!(StatusCode::Custom(599, "".into()), StatusCode::from_str("599"));
+        assert_eq!(StatusCode::InternalServerError, StatusCode::from_str("abc"));
+        assert_eq!(StatusCode::InternalServerError, StatusCode::from_str("1000"));
+    }
+
+    #[test]
+    fn classes_and_display() {
+        assert!(StatusCode::EarlyHints.is_informational());
+        assert!(StatusCode::NoContent.is_success());
+        assert!(StatusCode::NotModified.is_redirection());
+        assert!(StatusCode::NotFound.is_client_error() && !StatusCode::NotFound.is_server_error());
+        assert!(StatusCode::GatewayTimeout.is_server_error());
+        assert!(!StatusCode::Custom(999, "".into()).is_server_error());
+        assert_eq!("400 Bad Request", StatusCode::BadRequest.to_string());
+        assert_eq!("299", StatusCode::Custom(299, "".into()).to_string());
+        assert_eq!("299 Cached", StatusCode::Custom(299, "Cached".into()).to_string());
+    }
+
+}
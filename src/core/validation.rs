@@ -0,0 +1,257 @@
+use std::fmt;
+
+use crate::core::status::StatusCode;
+use crate::server::response::Response;
+
+
+/// Expected type of a url param, header, cookie or form field declared on a Route.
+#[derive(Clone, Debug)]
+pub enum ParamKind {
+    /// Any value. `min` and `max` apply to its length.
+    Text,
+    /// A signed integer. `min` and `max` apply to its value.
+    Integer,
+    /// `true` or `false`.
+    Bool,
+    /// An hyphenated UUID: 550e8400-e29b-41d4-a716-446655440000
+    Uuid,
+    /// One of the values listed.
+    OneOf(Vec<String>),
+    /// A value matching the regex. `min` and `max` apply to its length.
+    #[cfg(feature = "regex")]
+    Regex(regex::Regex),
+}
+
+/// Rule validating a value declared on a Route, e.g. `ParamRule::integer().min(1).max(100)`.
+/// A value that breaks the rule makes the server return a 400 Bad Request naming the value and the reason.
+#[derive(Clone, Debug)]
+pub struct ParamRule {
+    #[doc(hidden)]
+    pub kind: ParamKind,
+    #[doc(hidden)]
+    pub min: Option<i64>,
+    #[doc(hidden)]
+    pub max: Option<i64>,
+    #[doc(hidden)]
+    pub required: bool,
+}
+
+impl ParamRule {
+
+    fn new(kind: ParamKind) -> Self {
+        ParamRule { kind, min: None, max: None, required: true }
+    }
+
+    pub fn text() -> Self {
+        Self::new(ParamKind::Text)
+    }
+
+    pub fn integer() -> Self {
+        Self::new(ParamKind::Integer)
+    }
+
+    pub fn bool() -> Self {
+        Self::new(ParamKind::Bool)
+    }
+
+    pub fn uuid() -> Self {
+        Self::new(ParamKind::Uuid)
+    }
+
+    pub fn one_of(values: &[&str]) -> Self {
+        Self::new(ParamKind::OneOf(values.iter().map(|v| v.to_string()).collect()))
+    }
+
+    /// Will panic if the pattern is not a valid regex, as a Route is declared at start-up.
+    #[cfg(feature = "regex")]
+    pub fn regex(pattern: &str) -> Self {
+        Self::new(ParamKind::Regex(regex::Regex::new(pattern).expect("Invalid regex declared in a ParamRule")))
+    }
+
+    /// Minimum value of an integer, or minimum length of a text.
+    pub fn min(mut self, min: i64) -> Self {
+        self.min = Some(min);
+        self
+    }
+
+    /// Maximum value of an integer, or maximum length of a text.
+    pub fn max(mut self, max: i64) -> Self {
+        self.max = Some(max);
+        self
+    }
+
+    /// The value will only be validated if present.
+    pub fn optional(mut self) -> Self {
+        self.required = false;
+        self
+    }
+
+    /// Will return the reason why the value breaks the rule, if it does.
+    pub fn check(&self, value: &str) -> Result<(), String> {
+        let measured: i64 = match &self.kind {
+            ParamKind::Integer => value.parse::<i64>().map_err(|_| "must be an integer".to_string())?,
+            ParamKind::Bool => {
+                return match value {
+                    "true" | "false" => Ok(()),
+                    _ => Err("must be true or false".into()),
+                }
+            }
+            ParamKind::Uuid => {
+                return match is_uuid(value) {
+                    true => Ok(()),
+                    false => Err("must be a UUID".into()),
+                }
+            }
+            ParamKind::OneOf(values) => {
+                return match values.iter().any(|v| v == value) {
+                    true => Ok(()),
+                    false => Err(format!("must be one of: {}", values.join(", "))),
+                }
+            }
+            #[cfg(feature = "regex")]
+            ParamKind::Regex(regex) => {
+                if !regex.is_match(value) {
+                    return Err(format!("must match {}", regex.as_str()));
+                }
+                value.chars().count() as i64
+            }
+            ParamKind::Text => value.chars().count() as i64,
+        };
+
+        let unit = match self.kind {
+            ParamKind::Integer => "",
+            _ => " characters long",
+        };
+        match (self.min, self.max) {
+            (Some(min), _) if measured < min => Err(format!("must be at least {}{}", min, unit)),
+            (_, Some(max)) if measured > max => Err(format!("must be at most {}{}", max, unit)),
+            _ => Ok(()),
+        }
+    }
+}
+
+// 8-4-4-4-12 hexadecimal digits.
+fn is_uuid(value: &str) -> bool {
+    let groups: Vec<&str> = value.split('-').collect();
+    groups.len() == 5 && groups.iter().zip([8, 4, 4, 4, 12]).all(|(g, len)| g.len() == len && g.bytes().all(|b| b.is_ascii_hexdigit()))
+}
+
+
+/// Where the value that failed the validation was expected.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ParamLocation {
+    Query,
+    Header,
+    Cookie,
+    Form,
+}
+
+impl fmt::Display for ParamLocation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let msg = match self {
+            ParamLocation::Query => "query",
+            ParamLocation::Header => "header",
+            ParamLocation::Cookie => "cookie",
+            ParamLocation::Form => "form",
+        };
+        f.write_str(msg)
+    }
+}
+
+
+/// Value of a Request that failed the validation of a Route.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ValidationError {
+    pub location: ParamLocation,
+    pub name: String,
+    pub reason: String,
+}
+
+impl ValidationError {
+
+    pub fn new(location: ParamLocation, name: &str, reason: &str) -> Self {
+        ValidationError { location, name: name.into(), reason: reason.into() }
+    }
+
+    /// Will generate a 400 Bad Request Response with a JSON body:
+    /// {"error": "Bad Request", "location": "query", "parameter": "page", "reason": "must be an integer"}
+    pub fn into_response(self) -> Response {
+        let mut response = Response::default();
+        response.status = StatusCode::BadRequest;
+        response.set_body(format!("{{\"error\":\"Bad Request\",\"location\":\"{}\",\"parameter\":\"{}\",\"reason\":\"{}\"}}",
+            self.location, escape(&self.name), escape(&self.reason)));
+        response
+    }
+}
+
+impl fmt::Display for ValidationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} {} {}", self.location, self.name, self.reason)
+    }
+}
+
+// Escape a string to be written in a JSON string. (RFC 8259 - 7)
+pub(crate) fn escape(s: &str) -> String {
+    let mut escaped = String::with_capacity(s.len());
+    for c in s.chars() {
+        match c {
+            '"' => escaped.push_str("\\\""),
+            '\\' => escaped.push_str("\\\\"),
+            '\n' => escaped.push_str("\\n"),
+            '\r' => escaped.push_str("\\r"),
+            '\t' => escaped.push_str("\\t"),
+            c if c.is_control() => escaped.push_str(&format!("\\u{:04x}", c as u32)),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+
+#[cfg(test)]
+mod test {
+
+    use super::*;
+
+    #[test]
+    fn integer() {
+        let rule = ParamRule::integer().min(1).max(100);
+        assert_eq!(Ok(()), rule.check("42"));
+        assert_eq!(Err("must be an integer".to_string()), rule.check("forty-two"));
+        assert_eq!(Err("must be at least 1".to_string()), rule.check("0"));
+        assert_eq!(Err("must be at most 100".to_string()), rule.check("101"));
+    }
+
+    #[test]
+    fn text_length() {
+        let rule = ParamRule::text().min(3);
+        assert_eq!(Ok(()), rule.check("Léa"));
+        assert_eq!(Err("must be at least 3 characters long".to_string()), rule.check("Lé"));
+    }
+
+    #[test]
+    fn bool_uuid_and_values() {
+        assert_eq!(Ok(()), ParamRule::bool().check("true"));
+        assert!(ParamRule::bool().check("yes").is_err());
+        assert_eq!(Ok(()), ParamRule::uuid().check("550e8400-e29b-41d4-a716-446655440000"));
+        assert!(ParamRule::uuid().check("550e8400-e29b-41d4-a716-44665544000g").is_err());
+        assert_eq!(Err("must be one of: asc, desc".to_string()), ParamRule::one_of(&["asc", "desc"]).check("up"));
+    }
+
+    #[cfg(feature = "regex")]
+    #[test]
+    fn regex() {
+        let rule = ParamRule::regex("^[a-z]+$").max(5);
+        assert_eq!(Ok(()), rule.check("abc"));
+        assert_eq!(Err("must match ^[a-z]+$".to_string()), rule.check("ABC"));
+        assert!(rule.check("abcdef").is_err());
+    }
+
+    #[test]
+    fn response_body() {
+        let response = ValidationError::new(ParamLocation::Query, "page", "must be an \"integer\"").into_response();
+        assert_eq!(StatusCode::BadRequest, response.status);
+        assert_eq!(Some(r#"{"error":"Bad Request","location":"query","parameter":"page","reason":"must be an \"integer\""}"#), response.text());
+    }
+
+}
@@ -1,5 +1,9 @@
 use std::sync::Arc;
 
+use log::debug;
+
+use crate::core::authorization::{basic_challenge, bearer_challenge, Authorization};
+use crate::core::principal::Principal;
 use crate::core::status::StatusCode;
 
 use super::request::Request;
@@ -10,6 +14,16 @@ use super::request::Request;
     // /**  - Start with this
 
 
+/// Function executed by a Checkpoint or a Route check. It receives the Request that will be given to the Route Response,
+/// and can attach values to it with `request.insert_extension`.
+pub type Check = Arc<dyn Fn(&mut Request) -> Result<(), StatusCode> + Send + Sync>;
+
+/// Function receiving the user and the password of the Basic scheme, returning the Principal they identify if they are valid.
+pub type CredentialProvider = Arc<dyn Fn(&str, &str) -> Option<Principal> + Send + Sync>;
+
+/// Function receiving the token of the Bearer scheme, returning the Principal it identifies if it is valid.
+pub type TokenValidator = Arc<dyn Fn(&str) -> Option<Principal> + Send + Sync>;
+
 /// Struct to filter request before responding.
 #[derive(Clone)]
 pub struct Checkpoint {
@@ -18,16 +32,59 @@ pub struct Checkpoint {
     #[doc(hidden)]
     pub except: Vec<String>,
     #[doc(hidden)]
-    pub check: Arc<dyn Fn(Request) -> Result<(), StatusCode> + Send + Sync>
+    pub check: Check,
 }
 
 impl Checkpoint {
 
     /// Create a new Checkpoint.
-    pub fn new(paths: Vec<String>, check: Arc< dyn Fn(Request) -> Result<(), StatusCode> + Send + Sync> ) -> Self {
+    pub fn new(paths: Vec<String>, check: Check) -> Self {
         Checkpoint { paths, check, except: Vec::new()}
     }
 
+    /// Create a Checkpoint requiring the Basic scheme, the credentials being verified by the provider.
+    /// Missing or invalid credentials are answered with a 401 Unauthorized asking for the Basic scheme on the realm.
+    /// The Authorization and the Principal are then attached to the Request: `request.extension::<Principal>()`.
+    pub fn basic_auth(paths: Vec<String>, realm: &str, provider: CredentialProvider) -> Self {
+        let challenge = basic_challenge(realm);
+        Self::new(paths, Arc::new(move |request: &mut Request| {
+            match request.authorization() {
+                Some(Authorization::Basic { user, password }) => match provider(&user, &password) {
+                    Some(principal) => {
+                        request.insert_extension(Authorization::Basic { user, password });
+                        request.insert_extension(principal);
+                        Ok(())
+                    }
+                    None => {
+                        debug!("Basic credentials of user {} have been refused.", user);
+                        Err(StatusCode::Unauthorized(challenge.clone()))
+                    }
+                },
+                _ => Err(StatusCode::Unauthorized(challenge.clone())),
+            }
+        }))
+    }
+
+    /// Create a Checkpoint requiring the Bearer scheme, the token being verified by the validator.
+    /// A missing token is answered with a 401 Unauthorized asking for the Bearer scheme on the realm, an invalid one adds the `invalid_token` error.
+    /// The Authorization and the Principal are then attached to the Request: `request.extension::<Principal>()`.
+    pub fn bearer_auth(paths: Vec<String>, realm: &str, validator: TokenValidator) -> Self {
+        let (missing, invalid) = (bearer_challenge(realm, None), bearer_challenge(realm, Some("invalid_token")));
+        Self::new(paths, Arc::new(move |request: &mut Request| {
+            match request.authorization() {
+                Some(Authorization::Bearer(token)) => match validator(&token) {
+                    Some(principal) => {
+                        request.insert_extension(Authorization::Bearer(token));
+                        request.insert_extension(principal);
+                        Ok(())
+                    }
+                    None => Err(StatusCode::Unauthorized(invalid.clone())),
+                },
+                _ => Err(StatusCode::Unauthorized(missing.clone())),
+            }
+        }))
+    }
+
     /// Add a path to the exception list.
     pub fn except(&mut self, path: &str) {
         self.except.push(path.into())
@@ -68,7 +68,7 @@ impl CORSHandler {
             let mut string_returned = "".to_string();
             if !&v.is_empty() {
                 for m in v {
-                    string_returned.push_str(& format!("{} ", m.to_string()));
+                    string_returned.push_str(& format!("{} ", m));
                 }
                 // The last space is erased to not end with a ','
                 return string_returned.trim_end().replace(" ", ", ");
@@ -86,10 +86,10 @@ impl CORSHandler {
 
         };
  
-        response.add_header("Access-Control-Allow-Headers".into(), formatter(&self.headers_allowed));
-        response.add_header("Access-Control-Allow-Methods".into(), formatter(&methodify(&self.methods_allowed)));
-        response.add_header("Access-Control-Max-Age".into(), self.max_age.to_string());
-        response.add_header("Access-Control-Allow-Origin".into(), self.origin.first().unwrap().to_string());
+        response.set_header("Access-Control-Allow-Headers", &formatter(&self.headers_allowed));
+        response.set_header("Access-Control-Allow-Methods", &formatter(&methodify(&self.methods_allowed)));
+        response.set_header("Access-Control-Max-Age", &self.max_age.to_string());
+        response.set_header("Access-Control-Allow-Origin", self.origin.first().unwrap());
         Ok(response)
     }
 
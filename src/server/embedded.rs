@@ -0,0 +1,266 @@
+use std::fmt::Write as _;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use crate::core::{content::ContentType, etag::ETag, form::decode, method::HttpMethod, status::StatusCode};
+
+use super::{request::Request, response::Response, route::Route, static_files::directory_location};
+
+
+/// File compiled into the binary, with its precompressed variants if they were found next to it.
+#[derive(Debug)]
+pub struct EmbeddedFile {
+    /// Path from the embedded directory, starting with `/`.
+    pub path: &'static str,
+    pub bytes: &'static [u8],
+    /// Hash of the bytes, computed when compiling.
+    pub etag: &'static str,
+    /// Content of `path.gz`.
+    pub gzip: Option<&'static [u8]>,
+    /// Content of `path.br`.
+    pub brotli: Option<&'static [u8]>,
+}
+
+/// Serves files compiled into the binary under a url prefix, as `StaticFiles` serves a directory, for single-binary deployments.
+/// The files are generated by `generate` from a build script, and included with `embedded_files!`.
+#[derive(Clone, Debug)]
+pub struct EmbeddedAssets {
+    #[doc(hidden)]
+    pub prefix: String,
+    #[doc(hidden)]
+    pub files: &'static [EmbeddedFile],
+    #[doc(hidden)]
+    pub index: Option<String>,
+}
+
+impl EmbeddedAssets {
+
+    /// Will serve the files under the url `prefix`. Directories are served with their `index.html`.
+    pub fn new(prefix: &str, files: &'static [EmbeddedFile]) -> Self {
+        EmbeddedAssets { prefix: prefix.trim_end_matches('/').to_lowercase(), files, index: Some("index.html".into()) }
+    }
+
+    /// Will replace the file served for a directory.
+    pub fn index(mut self, name: &str) -> Self {
+        self.index = Some(name.into());
+        self
+    }
+
+    /// Directories will receive a 404 Not Found.
+    pub fn without_index(mut self) -> Self {
+        self.index = None;
+        self
+    }
+
+    /// Will return the GET Route serving the files, on `prefix/**`. The prefix itself is redirected to `prefix/`.
+    pub fn route(&self) -> Route {
+        let mut route = Route::new(&format!("{}/**", self.prefix), HttpMethod::GET);
+        let assets = self.clone();
+        route.set_response(Arc::new(move |req: Request| assets.respond(&req)));
+        route
+    }
+
+    /// Will return the file targeted by the Request, precompressed if the client accepts it. Returns a 404 Not Found if it is missing.
+    pub fn respond(&self, request: &Request) -> Result<Response, StatusCode> {
+        let relative = request.path.get(self.prefix.len()..).ok_or(StatusCode::NotFound)?;
+        let path = normalize(relative).ok_or(StatusCode::NotFound)?;
+        let file = match self.find(&path) {
+            Some(file) if !relative.ends_with('/') => file,
+            _ => {
+                let index = self.index.as_ref().ok_or(StatusCode::NotFound)?;
+                let file = self.find(&format!("{}/{}", path.trim_end_matches('/'), index)).ok_or(StatusCode::NotFound)?;
+                // Relative links of the index are resolved from the directory.
+                if !request.path.ends_with('/') {
+                    return Err(StatusCode::MovedPermanently(directory_location(&request.path[..self.prefix.len()], relative, request.query.as_deref())));
+                }
+                file
+            }
+        };
+
+        let mut offered = Vec::new();
+        if file.brotli.is_some() {
+            offered.push("br");
+        }
+        if file.gzip.is_some() {
+            offered.push("gzip");
+        }
+        offered.push("identity");
+
+        let mut response = Response::default();
+        let extension = file.path.rsplit_once('.').map(|(_, e)| e).filter(|e| !e.contains('/')).unwrap_or_default();
+        response.set_content_type(ContentType::from_extension(extension));
+        let (bytes, coding) = match request.negotiate_encoding(&offered).as_deref() {
+            Some("br") => (file.brotli.unwrap_or_default(), Some("br")),
+            Some("gzip") => (file.gzip.unwrap_or_default(), Some("gzip")),
+            _ => (file.bytes, None),
+        };
+        match coding {
+            // Each encoding is a representation of its own, with its own ETag.
+            Some(coding) => {
+                response.set_header("Content-Encoding", coding);
+                response.set_etag(&ETag::strong(&format!("{}-{}", file.etag, coding)));
+            }
+            None => response.set_etag(&ETag::strong(file.etag)),
+        }
+        if offered.len() > 1 {
+            response.set_header("Vary", "Accept-Encoding");
+        }
+        response.set_accept_ranges();
+        response.set_body_bytes(bytes.to_vec());
+        Ok(response)
+    }
+
+    fn find(&self, path: &str) -> Option<&'static EmbeddedFile> {
+        self.files.iter().find(|f| f.path == path)
+    }
+}
+
+// Decoded path starting with `/`. `..` is refused.
+fn normalize(relative: &str) -> Option<String> {
+    let mut path = String::new();
+    for segment in relative.split('/') {
+        match decode(segment.as_bytes(), false).as_str() {
+            "" | "." => continue,
+            ".." => return None,
+            s => {
+                path.push('/');
+                path.push_str(s);
+            }
+        }
+    }
+    Some(path)
+}
+
+
+/// To call from a build script: will write to `out` the list of the files of `dir`, each included with `include_bytes!`.
+/// A file `x.gz` or `x.br` next to a file `x` is embedded as its precompressed variant.
+///
+/// ```ignore
+/// // build.rs
+/// fn main() {
+///     let out = std::path::Path::new(&std::env::var("OUT_DIR").unwrap()).join("assets.rs");
+///     arkos::server::embedded::generate("dist", out).unwrap();
+/// }
+/// ```
+pub fn generate<D: AsRef<Path>, O: AsRef<Path>>(dir: D, out: O) -> io::Result<()> {
+    let dir = dir.as_ref().canonicalize()?;
+    let mut files = Vec::new();
+    collect(&dir, &mut files)?;
+    files.sort();
+
+    let mut code = String::from("&[\n");
+    for file in &files {
+        let name = file.to_string_lossy();
+        if (name.ends_with(".gz") || name.ends_with(".br")) && files.contains(&file.with_extension("")) {
+            continue;
+        }
+        let relative = file.strip_prefix(&dir).map_err(io::Error::other)?;
+        let path: String = relative.components().map(|c| format!("/{}", c.as_os_str().to_string_lossy())).collect();
+        let variant = |extension: &str| {
+            let variant = PathBuf::from(format!("{}.{}", name, extension));
+            match files.contains(&variant) {
+                true => format!("Some(include_bytes!({:?}))", variant),
+                false => "None".into(),
+            }
+        };
+        let etag = ETag::from_bytes(&fs::read(file)?).tag;
+        let _ = writeln!(code, "    ::arkos::server::embedded::EmbeddedFile {{ path: {:?}, bytes: include_bytes!({:?}), etag: {:?}, gzip: {}, brotli: {} }},", path, file, etag, variant("gz"), variant("br"));
+    }
+    code.push(']');
+    fs::write(out, code)?;
+    println!("cargo:rerun-if-changed={}", dir.display());
+    Ok(())
+}
+
+fn collect(dir: &Path, files: &mut Vec<PathBuf>) -> io::Result<()> {
+    for entry in fs::read_dir(dir)? {
+        let path = entry?.path();
+        if path.is_dir() {
+            collect(&path, files)?;
+        } else {
+            files.push(path);
+        }
+    }
+    Ok(())
+}
+
+/// Will include the files generated by `generate` in the `OUT_DIR` of the build script.
+///
+/// ```ignore
+/// static ASSETS: &[EmbeddedFile] = arkos::embedded_files!("assets.rs");
+/// server.add_route(EmbeddedAssets::new("/app", ASSETS).route());
+/// ```
+#[macro_export]
+macro_rules! embedded_files {
+    ($file:literal) => {
+        include!(concat!(env!("OUT_DIR"), "/", $file))
+    };
+}
+
+
+#[cfg(test)]
+mod test {
+
+    use super::*;
+
+    static FILES: &[EmbeddedFile] = &[
+        EmbeddedFile { path: "/app.js", bytes: b"console.log('arkos');", etag: "1a2b", gzip: Some(b"gzipped"), brotli: Some(b"brotli") },
+        EmbeddedFile { path: "/docs/index.html", bytes: b"<h1>Docs</h1>", etag: "3c4d", gzip: None, brotli: None },
+    ];
+
+    fn get(path: &str, accept_encoding: &str) -> Result<Response, StatusCode> {
+        let request = Request::parse(format!("GET {} HTTP/1.1\r\nHost: localhost\r\nAccept-Encoding: {}\r\n\r\n", path, accept_encoding)).unwrap();
+        EmbeddedAssets::new("/app", FILES).respond(&request)
+    }
+
+    #[test]
+    fn precompressed_variants() {
+        let response = get("/app/app.js", "gzip, br").unwrap();
+        assert_eq!(Some("brotli"), response.text());
+        assert_eq!(Some("br"), response.headers.get("Content-Encoding"));
+        assert_eq!(Some(ETag::strong("1a2b-br")), response.etag());
+        assert_eq!(Some("Accept-Encoding"), response.headers.get("Vary"));
+        assert_eq!(ContentType::Custom("text/javascript; charset=utf-8".into()), response.content_type);
+
+        assert_eq!(Some("gzipped"), get("/app/app.js", "gzip").unwrap().text());
+        let response = get("/app/app.js", "deflate").unwrap();
+        assert_eq!(Some("console.log('arkos');"), response.text());
+        assert_eq!(None, response.headers.get("Content-Encoding"));
+        assert_eq!(Some(ETag::strong("1a2b")), response.etag());
+    }
+
+    #[test]
+    fn index_and_missing_files() {
+        assert_eq!(Some("<h1>Docs</h1>"), get("/app/docs/", "").unwrap().text());
+        assert_eq!(None, get("/app/docs/", "").unwrap().headers.get("Vary"));
+        assert_eq!(Err(StatusCode::MovedPermanently("/app/docs/".into())), get("/app/docs", ""));
+        assert_eq!(Err(StatusCode::MovedPermanently("/app/docs/?lang=fr".into())), get("/app//docs?lang=fr", ""));
+        let request = Request::parse("GET //docs HTTP/1.1\r\nHost: localhost\r\n\r\n").unwrap();
+        assert_eq!(Err(StatusCode::MovedPermanently("/docs/".into())), EmbeddedAssets::new("/", FILES).respond(&request));
+        assert_eq!(Err(StatusCode::NotFound), get("/app/missing.js", ""));
+        assert_eq!(Err(StatusCode::NotFound), get("/app/docs/../app.js", ""));
+    }
+
+    #[test]
+    fn generate_list() {
+        let dir = std::env::temp_dir().join(format!("arkos-embedded-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(dir.join("public/css")).unwrap();
+        fs::write(dir.join("public/css/site.css"), "a").unwrap();
+        fs::write(dir.join("public/css/site.css.gz"), "gz").unwrap();
+        fs::write(dir.join("public/archive.gz"), "gz").unwrap();
+        generate(dir.join("public"), dir.join("assets.rs")).unwrap();
+        let code = fs::read_to_string(dir.join("assets.rs")).unwrap();
+        let root = dir.join("public").canonicalize().unwrap();
+        let _ = fs::remove_dir_all(&dir);
+
+        let lines: Vec<&str> = code.lines().collect();
+        assert_eq!(4, lines.len());
+        assert!(lines[1].contains("path: \"/archive.gz\"") && lines[1].ends_with("gzip: None, brotli: None },"));
+        assert!(lines[2].contains("path: \"/css/site.css\"") && lines[2].contains("etag: \"af63dc4c8601ec8c\""));
+        assert!(lines[2].contains(&format!("gzip: Some(include_bytes!({:?}))", root.join("css/site.css.gz"))));
+    }
+
+}
@@ -4,5 +4,10 @@ pub mod cors;
 pub mod response;
 pub mod request;
 pub mod checkpoint;
+pub mod state;
+pub mod static_files;
+pub mod embedded;
+#[cfg(feature = "jwt")]
+pub mod jwt;
 #[doc(hidden)]
 pub mod protocol;
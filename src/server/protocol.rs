@@ -13,7 +13,7 @@ impl Protocol {
 
     pub fn parse_from_raw(raw: &str) -> Result<Protocol, StatusCode> {
         let row = raw.split("\r\n").next().unwrap();
-        let end = row.split(' ').last();
+        let end = row.split(' ').next_back();
         Self::parse_from_str(end.unwrap())
     }
 
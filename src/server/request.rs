@@ -1,10 +1,26 @@
+use std::borrow::Cow;
 use std::collections::HashMap;
 
 
 use std::result::Result;
-
+use std::str::FromStr;
+use std::sync::Arc;
+
+use log::warn;
+
+use crate::core::authorization::Authorization;
+use crate::core::content::ContentType;
+use crate::core::extensions::Extensions;
+use crate::core::form::Form;
+use crate::core::header::HeaderMap;
+use crate::core::negotiation::{negotiate_charset, negotiate_content_type, negotiate_encoding, negotiate_language};
+use crate::core::range::ByteRangeSpec;
+use crate::core::multipart::{boundary, Multipart, MultipartLimits};
 use crate::core::status::StatusCode;
 use crate::core::method::HttpMethod;
+use crate::server::protocol::Protocol;
+use crate::server::state::State;
+use crate::wrapper::request_factory::{parse_http1x, request_line};
 
 #[derive(Debug, Clone)]
 pub struct Request {
@@ -14,81 +30,140 @@ pub struct Request {
     #[doc(hidden)]
     pub url: String,
     #[doc(hidden)]
-    pub headers : HashMap<String, String>,
+    pub path: String,
+    #[doc(hidden)]
+    pub headers : HeaderMap,
     #[doc(hidden)]
     pub cookies : HashMap<String, String>,
     #[doc(hidden)]
     pub param : HashMap<String, String>,
     #[doc(hidden)]
-    pub body : String,
+    pub query : Option<String>,
+    #[doc(hidden)]
+    pub body : Vec<u8>,
+    #[doc(hidden)]
+    pub extensions : Extensions,
+    #[doc(hidden)]
+    pub states : Arc<Extensions>,
 }
 
 impl Request {
 
-    /// Will parse a HTTP/1.1 request into a Request struct. If it fails, it will return a 500 Internal Server Error Response.
-
-    pub fn parse(raw_request: &str) -> Result<Request, StatusCode> {
-        // Firs thing we do is check to see if we have the `body` separator: \r\n\r\n
-        // and if we do, we take all the bytes up until the body separator.
-        //
-        // We also keep `pos` around so we can get the `body` at the end, by starting
-        // from the `pos` and taking the remaining bytes
-        let pos = raw_request.find("\r\n\r\n").unwrap_or(raw_request.len());
-
-        // Create a line iterator, meaning for every iteration we are looking at a new line
-        let mut parsed = raw_request[..pos].lines();
-
-        // Get the method and path iterator
-        let mut method_and_path = parsed.next().map(|line| line.split(' ')).ok_or(StatusCode::InternalServerError)?;
-        // ... and then get the method
-        let method = HttpMethod::from_str(method_and_path.next().ok_or(StatusCode::InternalServerError)?);
-        // ... and finally the path
-        let mut url_and_param = method_and_path.next().map(|line| line.split('?')).ok_or(StatusCode::InternalServerError)?;
-
-        let url = url_and_param.next().ok_or(StatusCode::InternalServerError)?;
-        let url = url.to_lowercase();
-
-        let params : HashMap<String, String> = match url_and_param.next() {
-            Some(param_line) => {
-                let mut params = HashMap::new();
-                for mut param in param_line.split('&').map(|c| c.splitn(2, '=')) {
-                    if let (Some(key), Some(value)) = (param.next(), param.next()) {
-                        params.insert(key.into(), value.into());
-                    }
-                };
-                params
-            },
-            None => HashMap::new(),
-        };
-        
-        
-
-
-        let mut headers: HashMap<String, String> = HashMap::new();
-        let mut cookies: HashMap<String, String> = HashMap::new();
-
-        // Cookie: yummy_cookie=choco; tasty_cookie=strawberry;
-        for row in parsed {
-            match row.strip_prefix("Cookie: ") {
-                Some(cookie_data) => {
-                    for mut data in cookie_data.split(';').map(|c| c.splitn(2, '=')) {
-                        if let (Some(key), Some(val)) = (data.next(), data.next()) {
-                            cookies.insert(key.into(), val.into());
-                        }
-                    }
-                }
-                None => {
-                    let mut header_data = row.split(":");
-                    if let (Some(name), Some(value)) = (header_data.next(), header_data.next()) {
-                        headers.insert(name.into(), value.into());
-                    }
-                }
+    /// Will parse a HTTP/1.x request into a Request struct.
+    /// A malformed request will return a 400 Bad Request, a request-target too long a 414 URI Too Long and
+    /// a header section too large a 431 Request Header Fields Too Large.
+    /// The body is kept as bytes: see `text` and `text_lossy` to read it as text. A body sent with a Content-Encoding is kept as sent:
+    /// the Server only decompresses it once a Route accepting the Request is found.
+    pub fn parse<R: AsRef<[u8]>>(raw_request: R) -> Result<Request, StatusCode> {
+        let raw_request = raw_request.as_ref();
+        match Protocol::parse_from_raw(&request_line(raw_request))? {
+            Protocol::Http1(v) => parse_http1x(raw_request, &v),
+            _ => Err(StatusCode::HTTPVersionNotSupported),
+        }
+    }
+
+    /// Will parse a url param into the type asked: `request.param_as::<u32>("page")`.
+    /// If missing or if it cannot be parsed, a 400 Bad Request is returned.
+    pub fn param_as<T: FromStr>(&self, name: &str) -> Result<T, StatusCode> {
+        self.param.get(name).and_then(|v| v.parse::<T>().ok()).ok_or(StatusCode::BadRequest)
+    }
+
+    /// Will parse a header into the type asked: `request.header_as::<u64>("X-Rate-Limit")`.
+    /// If missing or if it cannot be parsed, a 400 Bad Request is returned.
+    pub fn header_as<T: FromStr>(&self, name: &str) -> Result<T, StatusCode> {
+        self.headers.get_as::<T>(name).ok_or(StatusCode::BadRequest)
+    }
+
+    /// Will attach a value to the Request, to be read further down: in a Route check or the Route Response.
+    /// A value of the same type previously inserted is replaced.
+    pub fn insert_extension<T: Send + Sync + 'static>(&mut self, value: T) {
+        self.extensions.insert(value);
+    }
+
+    /// Will return the value of this type attached to the Request, e.g. by a Checkpoint.
+    pub fn extension<T: Send + Sync + 'static>(&self) -> Option<&T> {
+        self.extensions.get::<T>()
+    }
+
+    /// Will return the state of this type registered on the Server with `Server::with_state`.
+    /// If no state of this type has been registered, a 500 Internal Server Error is returned.
+    pub fn state<T: Send + Sync + 'static>(&self) -> Result<State<T>, StatusCode> {
+        match self.states.get_arc::<T>() {
+            Some(state) => Ok(State(state)),
+            None => {
+                warn!("No state of type {} has been registered on the Server.", std::any::type_name::<T>());
+                Err(StatusCode::InternalServerError)
             }
         }
-        
-        let body_start = (pos + 4).min(raw_request.len());
-        let body = raw_request[body_start..].to_string();
-        Ok(Request {method, url: url.to_string(), headers, cookies, param: params, body})
     }
 
-}
\ No newline at end of file
+    /// Will return the credentials of the Authorization header. Returns None if missing or malformed.
+    pub fn authorization(&self) -> Option<Authorization> {
+        self.headers.get("Authorization").and_then(Authorization::parse)
+    }
+
+    /// Will return the ranges of bytes asked by the Range header. Returns None if missing, malformed or not in bytes.
+    pub fn range(&self) -> Option<Vec<ByteRangeSpec>> {
+        self.headers.get("Range").and_then(ByteRangeSpec::parse)
+    }
+
+    /// Will return the Content-Type of the Request, if specified.
+    pub fn content_type(&self) -> Option<ContentType> {
+        self.headers.content_type().map(ContentType::from_header)
+    }
+
+    /// Will return the Content-Type the client prefers among those offered, according to its Accept header:
+    /// `request.negotiate(&[ContentType::Json, ContentType::Text])`. Without an Accept header, the first offered is returned.
+    /// Returns None if none is acceptable, which should lead to a 406 Not Acceptable.
+    pub fn negotiate(&self, offered: &[ContentType]) -> Option<ContentType> {
+        negotiate_content_type(&self.headers.get_all("Accept"), offered)
+    }
+
+    /// Will return the language the client prefers among the tags offered, according to its Accept-Language header.
+    pub fn negotiate_language(&self, offered: &[&str]) -> Option<String> {
+        negotiate_language(&self.headers.get_all("Accept-Language"), offered)
+    }
+
+    /// Will return the content coding the client prefers among those offered, according to its Accept-Encoding header.
+    pub fn negotiate_encoding(&self, offered: &[&str]) -> Option<String> {
+        negotiate_encoding(&self.headers.get_all("Accept-Encoding"), offered)
+    }
+
+    /// Will return the charset the client prefers among those offered, according to its Accept-Charset header.
+    pub fn negotiate_charset(&self, offered: &[&str]) -> Option<String> {
+        negotiate_charset(&self.headers.get_all("Accept-Charset"), offered)
+    }
+
+    /// Will parse the body as an application/x-www-form-urlencoded form, as sent by HTML forms.
+    /// If the Content-Type is not application/x-www-form-urlencoded, a 415 Unsupported Media Type is returned.
+    pub fn form(&self) -> Result<Form, StatusCode> {
+        match self.content_type() {
+            Some(ContentType::FormUrlEncoded) => Ok(Form::parse(&self.body)),
+            _ => Err(StatusCode::UnsupportedMediaType),
+        }
+    }
+
+    /// Will parse the body as a multipart/form-data body, as sent by HTML forms uploading files, with the default limits.
+    /// If the Content-Type is not multipart/form-data, a 415 Unsupported Media Type is returned.
+    pub fn multipart(&self) -> Result<Multipart, StatusCode> {
+        self.multipart_with(&MultipartLimits::default())
+    }
+
+    /// Will parse the body as a multipart/form-data body with the limits provided.
+    /// A body or a part over the limits returns a 413 Payload Too Large.
+    pub fn multipart_with(&self, limits: &MultipartLimits) -> Result<Multipart, StatusCode> {
+        let boundary = self.headers.content_type().and_then(boundary).ok_or(StatusCode::UnsupportedMediaType)?;
+        Multipart::parse(&self.body, &boundary, limits)
+    }
+
+    /// Will return the body as text. If the body is not valid UTF-8, a 400 Bad Request is returned.
+    pub fn text(&self) -> Result<&str, StatusCode> {
+        std::str::from_utf8(&self.body).map_err(|_| StatusCode::BadRequest)
+    }
+
+    /// Will return the body as text, invalid UTF-8 sequences being replaced by U+FFFD.
+    pub fn text_lossy(&self) -> Cow<'_, str> {
+        String::from_utf8_lossy(&self.body)
+    }
+
+}
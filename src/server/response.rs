@@ -1,7 +1,5 @@
-use std::collections::HashMap;
-
-
-use crate::{core::{content::ContentType, status::{StatusCode}, cookie::Cookie}};
+use crate::{core::{content::ContentType, status::{StatusCode}, cookie::Cookie, header::HeaderMap, date::HttpDate, etag::ETag, body::{BodyFile, BodyStream}}};
+use std::io::Read;
 
 
 
@@ -14,29 +12,67 @@ pub struct Response {
     #[doc(hidden)]
     pub content_type: ContentType,
     #[doc(hidden)]
-    pub headers: HashMap<String, String>,
+    pub headers: HeaderMap,
     #[doc(hidden)]
     pub cookies: Vec<Cookie>,
     #[doc(hidden)]
-    pub body: String,
+    pub body: Vec<u8>,
+    #[doc(hidden)]
+    pub stream: Option<BodyStream>,
+    #[doc(hidden)]
+    pub file: Option<BodyFile>,
 }
 
 impl Response {
 
     /// Will return a Response with an empty body, a Ok Response and a Content-Type of application/json.
     pub fn default() -> Self {
-        Response {status: StatusCode::Ok, content_type: ContentType::Json, headers: HashMap::new(), cookies: Vec::new(), body: "".to_string()}
+        Response {status: StatusCode::Ok, content_type: ContentType::Json, headers: HeaderMap::new(), cookies: Vec::new(), body: Vec::new(), stream: None, file: None}
     }
     
     /// To use to set the body of the Response.
     pub fn set_body(&mut self, body: String) -> &mut Response {
+        self.body = body.into_bytes();
+        self
+    }
+
+    /// To use to set a binary body to the Response, as an image or a PDF.
+    pub fn set_body_bytes(&mut self, body: Vec<u8>) -> &mut Response {
         self.body = body;
         self
     }
 
-    /// Will add a header to the Response. 
+    /// To use to send a body while it is read, as a large file or a CSV export, without buffering it.
+    /// The body set with `set_body` is then ignored, and no Content-Length is sent. Ranges are not served for a streamed body.
+    pub fn set_body_stream<R: Read + Send + 'static>(&mut self, reader: R) -> &mut Response {
+        self.body.clear();
+        self.file = None;
+        self.stream = Some(BodyStream::new(reader));
+        self
+    }
+
+    /// To use to send a file without loading it in memory. On Linux, it is transferred with `sendfile`.
+    /// Its length is known: it is sent with a Content-Length, and ranges are served from it.
+    pub fn set_body_file(&mut self, file: BodyFile) -> &mut Response {
+        self.body.clear();
+        self.stream = None;
+        self.file = Some(file);
+        self
+    }
+
+    /// Will return the body as text, if it is valid UTF-8.
+    pub fn text(&self) -> Option<&str> {
+        std::str::from_utf8(&self.body).ok()
+    }
+
+    /// Will add a header to the Response. If the header already has a value, both will be sent.
     /// Don't specify Content-Length. If you want to specify a Content-Type, use `set_content_type' instead.
     pub fn add_header(&mut self, key: String, val: String) {
+        self.headers.append(&key, &val);
+    }
+
+    /// Will set a header to the Response, replacing any value previously added under the same name.
+    pub fn set_header(&mut self, key: &str, val: &str) {
         self.headers.insert(key, val);
     }
 
@@ -45,6 +81,32 @@ impl Response {
         self.cookies.push(cookie);
     }
 
+    /// Will set the ETag header, validating the representation sent. GET and HEAD Requests matching it with If-None-Match receive a 304 Not Modified.
+    pub fn set_etag(&mut self, etag: &ETag) {
+        self.headers.insert("ETag", &etag.to_string());
+    }
+
+    /// Will return the ETag header, if set and valid.
+    pub fn etag(&self) -> Option<ETag> {
+        self.headers.get("ETag").and_then(ETag::parse)
+    }
+
+    /// Will set the Last-Modified header. GET and HEAD Requests with an If-Modified-Since as recent receive a 304 Not Modified.
+    pub fn set_last_modified(&mut self, date: HttpDate) {
+        self.headers.insert("Last-Modified", &date.to_string());
+    }
+
+    /// Will return the Last-Modified header, if set and valid.
+    pub fn last_modified(&self) -> Option<HttpDate> {
+        self.headers.get("Last-Modified").and_then(HttpDate::parse)
+    }
+
+    /// Will set the Accept-Ranges header: the Range of a GET Request is then honoured,
+    /// the server answering with a 206 Partial Content, or a 416 Range Not Satisfiable.
+    pub fn set_accept_ranges(&mut self) {
+        self.headers.insert("Accept-Ranges", "bytes");
+    }
+
     /// Will specify the Content-Type Header for the Response.
     /// In case of a enum variant missing, use the variant `Custom(String)`
     pub fn set_content_type(&mut self, content_type: ContentType){
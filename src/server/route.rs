@@ -2,15 +2,19 @@
 #![doc = include_str!( "../../docs/route.md")]
 
 
-use std::{collections::HashMap, sync::Arc};
+use std::fmt;
+use std::sync::Arc;
 
 use log::debug;
 
-use crate::core::{method::HttpMethod, status::StatusCode};
+use crate::core::{authorization::bearer_challenge, compression::Compression, conditional::Validators, content::ContentType, method::HttpMethod, principal::Principal, status::StatusCode, validation::{ParamLocation, ParamRule, ValidationError}};
 
-use super::{request::Request, response::Response};
+use super::{checkpoint::Check, request::Request, response::Response};
 
 
+/// Function returning the validators of the current representation of the resource targeted by the Request.
+pub type ValidatorsProvider = Arc<dyn Fn(&Request) -> Result<Validators, StatusCode> + Send + Sync>;
+
 #[derive(Clone)]
 pub struct Route{
     #[doc(hidden)]
@@ -26,16 +30,36 @@ pub struct Route{
     #[doc(hidden)]
     pub required_cookie: Vec<String>,
     #[doc(hidden)]
+    pub required_form_field: Vec<String>,
+    #[doc(hidden)]
+    pub param_rules: Vec<(ParamLocation, String, ParamRule)>,
+    #[doc(hidden)]
+    pub consumes: Vec<ContentType>,
+    #[doc(hidden)]
+    pub produces: Vec<ContentType>,
+    #[doc(hidden)]
+    pub required_roles: Vec<String>,
+    #[doc(hidden)]
+    pub required_scopes: Vec<String>,
+    #[doc(hidden)]
+    pub challenge: String,
+    #[doc(hidden)]
+    pub validators: Option<ValidatorsProvider>,
+    #[doc(hidden)]
+    pub auto_etag: bool,
+    #[doc(hidden)]
+    pub compression: Option<Compression>,
+    #[doc(hidden)]
     pub response: Arc<dyn Fn(Request) -> Result<Response, StatusCode> + Send + Sync>,
     #[doc(hidden)]
-    pub checks: Vec<Arc<dyn Fn(Request) -> Result<(), StatusCode> + Send + Sync>>,
+    pub checks: Vec<Check>,
 }
 
 impl Route {
     
     /// Will create a new Route with a path and a HTTP Method, but with an empty Response that will return a 200 Ok if called.
     pub fn new(url: &str,method: HttpMethod) -> Self{
-        Route {url : url.to_string(), method, request: None,  required_param: Vec::new(), required_header: Vec::new(),required_cookie: Vec::new(),  response : Arc::new(|_req: Request| {Ok(Response::default())}), checks: Vec::new() }
+        Route {url : url.to_string(), method, request: None,  required_param: Vec::new(), required_header: Vec::new(),required_cookie: Vec::new(), required_form_field: Vec::new(), param_rules: Vec::new(), consumes: Vec::new(), produces: Vec::new(), required_roles: Vec::new(), required_scopes: Vec::new(), challenge: bearer_challenge("arkos", None), validators: None, auto_etag: false, compression: None, response : Arc::new(|_req: Request| {Ok(Response::default())}), checks: Vec::new() }
     }
 
     /// Will add a required url parameters. If missing, the server will return a 400 Bad Request Response.
@@ -56,41 +80,220 @@ impl Route {
         self
     }
 
-    /// Will set a Response to the Route. If you want to return only a Status Code like 401 or 403, use Err(StatusCode::Unauthorized) instead. The server will generate a Response from it when calling your closure.
-    pub fn set_response<'a>(&mut self, fun: Arc<dyn Fn(Request) -> Result<Response, StatusCode> + Send + Sync>)  {
+    /// Will add a required field to the application/x-www-form-urlencoded body.
+    /// If missing, or if the body is not a form, the server will return a 400 Bad Request Response.
+    pub fn add_required_form_field(&mut self,  name: &str) -> &mut Self {
+        self.required_form_field.push(name.into());
+        self
+    }
+
+    /// Will validate a url param against a rule: `route.validate_url_param("page", ParamRule::integer().min(1))`.
+    /// If missing (unless the rule is optional) or invalid, the server will return a 400 Bad Request Response naming the param and the reason.
+    pub fn validate_url_param(&mut self, name: &str, rule: ParamRule) -> &mut Self {
+        self.param_rules.push((ParamLocation::Query, name.into(), rule));
+        self
+    }
+
+    /// Will validate a header against a rule: `route.validate_header("X-Request-Id", ParamRule::uuid())`.
+    /// If missing (unless the rule is optional) or invalid, the server will return a 400 Bad Request Response naming the header and the reason.
+    pub fn validate_header(&mut self, name: &str, rule: ParamRule) -> &mut Self {
+        self.param_rules.push((ParamLocation::Header, name.into(), rule));
+        self
+    }
+
+    /// Will validate a field of the application/x-www-form-urlencoded body against a rule: `route.validate_form_field("age", ParamRule::integer().min(18))`.
+    /// If missing (unless the rule is optional) or invalid, or if the body is not a form, the server will return a 400 Bad Request Response naming the field and the reason.
+    pub fn validate_form_field(&mut self, name: &str, rule: ParamRule) -> &mut Self {
+        self.param_rules.push((ParamLocation::Form, name.into(), rule));
+        self
+    }
+
+    /// Will declare a Content-Type the Route accepts in the body of the Request. Can be called multiple times.
+    /// If the Content-Type of the Request is none of them, the server will return a 415 Unsupported Media Type.
+    /// Routes sharing the same path and method can differ only by what they consume and produce.
+    pub fn consumes(&mut self, content_type: ContentType) -> &mut Self {
+        self.consumes.push(content_type);
+        self
+    }
+
+    /// Will declare a Content-Type the Route can respond with. Can be called multiple times.
+    /// If the Accept header of the Request accepts none of them, the server will return a 406 Not Acceptable.
+    /// The Response picks the one to use with `request.negotiate(&route.produces)`.
+    pub fn produces(&mut self, content_type: ContentType) -> &mut Self {
+        self.produces.push(content_type);
+        self
+    }
+
+    /// Will require the Principal attached to the Request to have at least one of the roles.
+    /// Without Principal, the server will return a 401 Unauthorized, and with a Principal missing every role, a 403 Forbidden.
+    pub fn requires_any_role(&mut self, roles: &[&str]) -> &mut Self {
+        self.required_roles.extend(roles.iter().map(|r| r.to_string()));
+        self
+    }
+
+    /// Will require the Principal attached to the Request to have the scope. Can be called multiple times, every scope being required.
+    /// Without Principal, the server will return a 401 Unauthorized, and with a Principal missing a scope, a 403 Forbidden.
+    pub fn requires_scope(&mut self, scope: &str) -> &mut Self {
+        self.required_scopes.push(scope.into());
+        self
+    }
+
+    /// Will replace the WWW-Authenticate challenge of the 401 Unauthorized sent when the Route requires a role or a scope
+    /// and no Principal is attached to the Request: `route.set_challenge(&basic_challenge("admin"))`. It asks for the Bearer scheme by default.
+    pub fn set_challenge(&mut self, challenge: &str) -> &mut Self {
+        self.challenge = challenge.into();
+        self
+    }
+
+    /// Will set a function returning the validators of the current representation of the resource, evaluated before the Response.
+    /// A Request whose preconditions are not met receives a 412 Precondition Failed before any change is applied by the Response,
+    /// and a GET or a HEAD matching them a 304 Not Modified without the Response being called.
+    pub fn set_validators(&mut self, fun: ValidatorsProvider) -> &mut Self {
+        self.validators = Some(fun);
+        self
+    }
+
+    /// Will add a strong ETag computed from the body to the 200 Ok Responses of GET and HEAD Requests, if the Response sets none.
+    pub fn auto_etag(&mut self) -> &mut Self {
+        self.auto_etag = true;
+        self
+    }
+
+    /// Will replace the compression of the Server for this Route, as `Compression::disabled()` for a Route streaming events.
+    pub fn set_compression(&mut self, compression: Compression) -> &mut Self {
+        self.compression = Some(compression);
+        self
+    }
+
+    /// Will return true if the Route answers this url: the same url, or any url under it if the Route url ends with `/**`.
+    pub fn matches(&self, url: &str) -> bool {
+        match self.url.strip_suffix("/**") {
+            Some(prefix) => url == prefix || url.strip_prefix(prefix).is_some_and(|rest| rest.starts_with('/')),
+            None => self.url == url,
+        }
+    }
+
+    /// Will set a Response to the Route. If you want to return only a Status Code like 401 or 403, use Err(StatusCode::Forbidden) instead. The server will generate a Response from it when calling your closure.
+    pub fn set_response(&mut self, fun: Arc<dyn Fn(Request) -> Result<Response, StatusCode> + Send + Sync>)  {
         self.response = fun;
     }
     
-    pub fn add_check(&mut self, check: Arc<dyn Fn(Request) -> Result<(), StatusCode> + Send + Sync>)  {
+    /// Will add a check executed before the Response. As a Checkpoint, it can attach values to the Request.
+    pub fn add_check(&mut self, check: Check)  {
         self.checks.push(check);
     }
         
     /// Will iterated throught every required fields to know if the Request is valid.
     /// Will tell you the missing field in the console if the debug level is allowed.
     pub fn is_request_valid(&self, request: &Request) -> bool {
+        match self.validate(request) {
+            Ok(_) => true,
+            Err(e) => {
+                debug!("Request is invalid: {}.", e);
+                false
+            }
+        }
+    }
+
+    /// Will check the Principal attached to the Request against the roles and scopes required by the Route.
+    pub fn authorize(&self, request: &Request) -> Result<(), StatusCode> {
+        if self.required_roles.is_empty() && self.required_scopes.is_empty() {
+            return Ok(());
+        }
+        let principal = request.extension::<Principal>().ok_or_else(|| StatusCode::Unauthorized(self.challenge.clone()))?;
+        let has_role = self.required_roles.is_empty() || self.required_roles.iter().any(|r| principal.has_role(r));
+        match has_role && self.required_scopes.iter().all(|s| principal.has_scope(s)) {
+            true => Ok(()),
+            false => Err(StatusCode::Forbidden),
+        }
+    }
+
+    /// Will describe what the Route requires, to review which Routes are protected by what.
+    pub fn describe(&self) -> RouteDescription {
+        RouteDescription {
+            method: self.method,
+            url: self.url.clone(),
+            roles: self.required_roles.clone(),
+            scopes: self.required_scopes.clone(),
+            consumes: self.consumes.iter().map(ContentType::get).collect(),
+            produces: self.produces.iter().map(ContentType::get).collect(),
+            checks: self.checks.len(),
+            checkpoints: 0,
+        }
+    }
+
+    /// Will check every required field and every rule declared, and return the first value that fails.
+    pub fn validate(&self, request: &Request) -> Result<(), ValidationError> {
 
-        let checker = |item: String, required: &Vec<String>, list: &HashMap<String, String>| -> bool {
-            if !required.is_empty() {
-                if list.is_empty(){
-                    return false;
-                } 
-                for header in required {
-                    if !list.contains_key(header) {
-                        debug!("{} {} is missing.", item, &header);
-                        return false;
-                    } 
-                }
+        let checker = |location: ParamLocation, required: &[String], contains: &dyn Fn(&str) -> bool| -> Result<(), ValidationError> {
+            match required.iter().find(|name| !contains(name)) {
+                Some(name) => Err(ValidationError::new(location, name, "is missing")),
+                None => Ok(()),
             }
-            true
         };
 
-        if !checker("Header".into(), &self.required_header, &request.headers) || 
-            !checker("Param".into(), &self.required_param, &request.param) ||
-            !checker("Cookie".into(), &self.required_cookie, &request.cookies) {
-            return false;
+        checker(ParamLocation::Header, &self.required_header, &|name| request.headers.contains_key(name))?;
+        checker(ParamLocation::Query, &self.required_param, &|name| request.param.contains_key(name))?;
+        checker(ParamLocation::Cookie, &self.required_cookie, &|name| request.cookies.contains_key(name))?;
+
+        // The body is decoded only if a form field is required or validated. The error names the first of them.
+        let form_field = self.required_form_field.first()
+            .or_else(|| self.param_rules.iter().find(|(location, _, _)| *location == ParamLocation::Form).map(|(_, name, _)| name));
+        let form = match form_field {
+            Some(name) => Some(request.form().map_err(|_| ValidationError::new(ParamLocation::Form, name, "the body must be an application/x-www-form-urlencoded form"))?),
+            None => None,
+        };
+        if let Some(form) = &form {
+            checker(ParamLocation::Form, &self.required_form_field, &|name| form.contains_key(name))?;
+        }
+
+        for (location, name, rule) in &self.param_rules {
+            let value = match location {
+                ParamLocation::Query => request.param.get(name).map(String::as_str),
+                ParamLocation::Header => request.headers.get(name),
+                ParamLocation::Cookie => request.cookies.get(name).map(String::as_str),
+                ParamLocation::Form => form.as_ref().and_then(|form| form.get(name)),
+            };
+            match value {
+                Some(value) => rule.check(value).map_err(|reason| ValidationError::new(*location, name, &reason))?,
+                None if rule.required => return Err(ValidationError::new(*location, name, "is missing")),
+                None => (),
+            }
         }
 
-        true
+        Ok(())
+    }
+}
+
+
+/// What a Route requires: the roles and scopes, the Content-Type consumed and produced, and the number of checks protecting it.
+/// Displayed as a line: `GET /orders roles=[admin] scopes=[orders:read] consumes=[] produces=[application/json] checks=0 checkpoints=1`
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RouteDescription {
+    pub method: HttpMethod,
+    pub url: String,
+    pub roles: Vec<String>,
+    pub scopes: Vec<String>,
+    pub consumes: Vec<String>,
+    pub produces: Vec<String>,
+    /// Route checks.
+    pub checks: usize,
+    /// Server Checkpoints whose paths cover the Route, filled by `Server::describe_routes`.
+    pub checkpoints: usize,
+}
+
+impl RouteDescription {
+
+    /// Will return true if the Route requires a role or a scope, or is covered by a check or a Checkpoint.
+    pub fn is_protected(&self) -> bool {
+        !self.roles.is_empty() || !self.scopes.is_empty() || self.checks > 0 || self.checkpoints > 0
+    }
+}
+
+impl fmt::Display for RouteDescription {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} {} roles=[{}] scopes=[{}] consumes=[{}] produces=[{}] checks={} checkpoints={}", self.method.to_string(), self.url,
+            self.roles.join(", "), self.scopes.join(", "), self.consumes.join(", "), self.produces.join(", "), self.checks, self.checkpoints)
     }
 }
 
@@ -1,5 +1,5 @@
 
-use std::io::{Read, Write};
+use std::io::Write;
 use std::net::TcpStream;
 use std::net::TcpListener;
 
@@ -7,17 +7,21 @@ use std::option::Option;
 
 use std::sync::{Mutex, Arc};
 
-use log::{error, info, trace};
+use log::{debug, error, info, trace};
 
 use tokio::task;
 
 
 use crate::handler::http1::handle_http1_request;
 use crate::server::cors::CORSHandler;
+use crate::core::extensions::Extensions;
+use crate::core::compression::Compression;
 
 
-use crate::core::status::{ StatusCode};
+use crate::core::status::{StatusCode, HttpStatusCode};
+use crate::wrapper::request_factory::{read_http1x, request_line, MAX_DECODED_BODY_SIZE};
 use crate::wrapper::response_factory::ResponseFactory;
+use crate::wrapper::checkpoint_manager::compare;
 
 
 
@@ -25,7 +29,23 @@ use super::checkpoint::Checkpoint;
 
 use super::protocol::Protocol;
 
-use super::route::Route;
+use super::route::{Route, RouteDescription};
+
+/// Settings of the Server shared by every Request it handles.
+#[doc(hidden)]
+#[derive(Clone)]
+pub struct ServerContext {
+    pub states: Arc<Extensions>,
+    pub compression: Compression,
+    pub server_header: Option<String>,
+    pub max_decoded_body_size: usize,
+}
+
+impl Default for ServerContext {
+    fn default() -> Self {
+        ServerContext { states: Arc::new(Extensions::new()), compression: Compression::default(), server_header: None, max_decoded_body_size: MAX_DECODED_BODY_SIZE }
+    }
+}
 
 pub struct Server{
     #[doc(hidden)]
@@ -38,6 +58,14 @@ pub struct Server{
     cors_handler: CORSHandler,
     #[doc(hidden)]
     checkpoints: Vec<Checkpoint>,
+    #[doc(hidden)]
+    states: Extensions,
+    #[doc(hidden)]
+    compression: Compression,
+    #[doc(hidden)]
+    server_header: Option<String>,
+    #[doc(hidden)]
+    max_decoded_body_size: usize,
 }
 
 
@@ -49,7 +77,7 @@ impl Server
 
     /// Will return an empty Server with an inert (deactivated) CORSHandler.
     pub fn new(address: [usize; 4], port: u32 ) -> Option<Server> {
-        Some(Server {address, port, routes: Vec::new(), cors_handler: CORSHandler::inert(), checkpoints: Vec::new()})
+        Some(Server {address, port, routes: Vec::new(), cors_handler: CORSHandler::inert(), checkpoints: Vec::new(), states: Extensions::new(), compression: Compression::new(), server_header: None, max_decoded_body_size: MAX_DECODED_BODY_SIZE})
     }
 
     /// Will set the routes as Arkos doesn't use a Router kind of struct.
@@ -58,17 +86,66 @@ impl Server
         self.routes = routes;
     }
 
+    /// Will add a Route to the ones already set, as the Route of `StaticFiles`.
+    pub fn add_route(&mut self, route: Route) {
+        self.routes.push(route);
+    }
+
     /// Will replace the CORSHandler. 
     pub fn set_cors_handler(&mut self, cors: CORSHandler) {
         self.cors_handler = cors;
     }
 
+    /// Will add a Checkpoint, executed on every Request matching its paths before searching for a Route.
+    pub fn add_checkpoint(&mut self, checkpoint: Checkpoint) {
+        self.checkpoints.push(checkpoint);
+    }
+
+    /// Will register a state shared by every Request, as a database pool or the configuration.
+    /// Checks and Route Responses read it with `request.state::<T>()`. A state of the same type previously registered is replaced.
+    pub fn with_state<T: Send + Sync + 'static>(&mut self, state: T) -> &mut Self {
+        self.states.insert(state);
+        self
+    }
+
+    /// Will replace the compression of the Responses. By default, every content coding enabled by a feature is used,
+    /// for bodies of at least 1024 bytes. A Route can override it.
+    pub fn set_compression(&mut self, compression: Compression) {
+        self.compression = compression;
+    }
+
+    /// Will send a Server header with every Response, as `arkos/0.1.2`. None is sent by default, not to disclose the software used.
+    /// A Response setting its own Server header keeps it.
+    pub fn set_server_header(&mut self, value: &str) {
+        self.server_header = Some(value.into());
+    }
+
+    /// Will replace the largest size of a body sent with a Content-Encoding once decompressed, 10 MiB by default.
+    /// A larger body is answered with a 413 Payload Too Large. It is only decompressed once a Route accepting it is found.
+    pub fn set_max_decoded_body_size(&mut self, size: usize) {
+        self.max_decoded_body_size = size;
+    }
+
+    /// Will describe every Route, with the number of Checkpoints covering its path, to review which Routes are protected by what.
+    pub fn describe_routes(&self) -> Vec<RouteDescription> {
+        self.routes.iter().map(|route| {
+            let mut description = route.describe();
+            description.checkpoints = self.checkpoints.iter()
+                .filter(|c| c.paths.iter().any(|p| compare(p, &route.url)) && !c.except.iter().any(|p| compare(p, &route.url)))
+                .count();
+            description
+        }).collect()
+    }
+
     /// Start up the server.
     pub fn serve(&self){
 
 
 
         info!("{} route(s) found.", &self.routes.len());
+        for description in self.describe_routes() {
+            debug!("{}", description);
+        }
         if self.cors_handler.activated {
             info!("CORS Handler is activated.")
         } else {
@@ -76,7 +153,7 @@ impl Server
         }
 
 
-        let address = format!("{:?}.{:?}.{:?}.{:?}:{:?}", &self.address.get(0).unwrap(), &self.address.get(1).unwrap(), &self.address.get(2).unwrap(), &self.address.get(3).unwrap(), &self.port);
+        let address = format!("{:?}.{:?}.{:?}.{:?}:{:?}", &self.address[0], &self.address[1], &self.address[2], &self.address[3], &self.port);
 
         let listener = match TcpListener::bind(&address){
             Ok(s) => {
@@ -90,6 +167,8 @@ impl Server
         };
 
 
+        let context = Arc::new(ServerContext { states: Arc::new(self.states.clone()), compression: self.compression.clone(), server_header: self.server_header.clone(), max_decoded_body_size: self.max_decoded_body_size });
+
         for stream in listener.incoming(){
 
             match stream {
@@ -99,9 +178,10 @@ impl Server
                     let routes = Arc::new(Mutex::new(self.routes.clone()));
                     let cors = Arc::new(Mutex::new(self.cors_handler.clone()));
                     let checkpoints = Arc::new(Mutex::new(self.checkpoints.clone()));
+                    let context = Arc::clone(&context);
                     let _handle = task::spawn(async {
 
-                        match handle_request(stream, routes, cors, checkpoints) {
+                        match handle_request(stream, routes, cors, checkpoints, context) {
                             Ok(_s) => trace!("Succesful handling of request."),
                             Err(_) => trace!("Failed to handle request."),
                         };
@@ -118,40 +198,82 @@ impl Server
 }
 
 #[doc(hidden)]
-fn handle_request(stream: Arc<Mutex<TcpStream>>, routes: Arc<Mutex<Vec<Route>>>, cors: Arc<Mutex<CORSHandler>>, checkpoints: Arc<Mutex<Vec<Checkpoint>>>) -> std::io::Result<()>{
+fn handle_request(stream: Arc<Mutex<TcpStream>>, routes: Arc<Mutex<Vec<Route>>>, cors: Arc<Mutex<CORSHandler>>, checkpoints: Arc<Mutex<Vec<Checkpoint>>>, context: Arc<ServerContext>) -> std::io::Result<()>{
     
     let mut stream = stream.lock().unwrap();
-    let mut buffer = [0; 1024];
-    let bytes_read = stream.read(&mut buffer)?;
+    let raw = match read_http1x(&mut *stream)? {
+        Ok(raw) => raw,
+        Err(e) => {
+            trace!("Request has been refused while being read - Returning {} {}", e.get_code(), e.get_title());
+            let mut response_factory = ResponseFactory::for_status_code(Protocol::Http1(1), e);
+            add_server_header(&mut response_factory, &context.server_header);
+            stream.write_all(&response_factory.consume())?;
+            return Ok(());
+        }
+    };
 
-    let b = String::from_utf8_lossy(&buffer[..bytes_read]);
 
 
 
 
-    let mut response_factory  : ResponseFactory = match Protocol::parse_from_raw(&b) {
-        Ok(protocol_parsed) => match protocol_parsed {
-            Protocol::Http1(v) => {
-                trace!("Request received has Protocol HTTP/1.{} - Routed for Request handling", v);
-                match handle_http1_request(&v, routes, &b, cors, checkpoints) {
-                    Ok(r) => r,
-                    Err(e) => ResponseFactory::for_status_code(Protocol::Http1(v), e),
-                }
-            },
-            _ => {            
-            trace!("Fail to know which Transfert Protocol Request used. Returning 505 HTTP Version Not Supported");
-            ResponseFactory::for_status_code(Protocol::Http1(0), StatusCode::HTTPVersionNotSupported)
-        }},
+    let mut response_factory  : ResponseFactory = match Protocol::parse_from_raw(&request_line(&raw)) {
+        Ok(Protocol::Http1(v)) => {
+            trace!("Request received has Protocol HTTP/1.{} - Routed for Request handling", v);
+            match handle_http1_request(&v, routes, &raw, cors, checkpoints, Arc::clone(&context)) {
+                Ok(r) => r,
+                Err(e) => ResponseFactory::for_status_code(Protocol::Http1(v), e),
+            }
+        },
         _ => {
             trace!("Fail to know which Transfert Protocol Request used. Returning 505 HTTP Version Not Supported");
             ResponseFactory::for_status_code(Protocol::Http1(0), StatusCode::HTTPVersionNotSupported)
         }
     };
 
+    add_server_header(&mut response_factory, &context.server_header);
+    response_factory.write_to(&mut *stream)
+}
+
+fn add_server_header(response_factory: &mut ResponseFactory, server_header: &Option<String>) {
+    if let Some(value) = server_header {
+        if !response_factory.response.headers.contains_key("Server") {
+            response_factory.response.headers.insert("Server", value);
+        }
+    }
+}
+
+
+#[cfg(test)]
+mod test {
 
-    let response: String = response_factory.consume();
+    use crate::core::{content::ContentType, method::HttpMethod};
 
-    stream.write(response.as_bytes())?;
+    use super::*;
+
+    #[test]
+    fn describe_routes() {
+        let mut orders = Route::new("/orders", HttpMethod::POST);
+        orders.requires_any_role(&["admin"]).requires_scope("orders:write").consumes(ContentType::Json);
+        let mut server = Server::new([127, 0, 0, 1], 8080).unwrap();
+        server.set_routes(vec![orders, Route::new("/health", HttpMethod::GET)]);
+        let mut checkpoint = Checkpoint::new(vec!["/**".into()], Arc::new(|_req: &mut crate::server::request::Request| Ok(())));
+        checkpoint.except("/health");
+        server.add_checkpoint(checkpoint);
+
+        let descriptions = server.describe_routes();
+        assert_eq!("POST /orders roles=[admin] scopes=[orders:write] consumes=[application/json] produces=[] checks=0 checkpoints=1", descriptions[0].to_string());
+        assert!(descriptions[0].is_protected());
+        assert!(!descriptions[1].is_protected());
+    }
+
+    #[test]
+    fn server_header() {
+        let mut response_factory = ResponseFactory::for_status_code(Protocol::Http1(1), StatusCode::NotFound);
+        add_server_header(&mut response_factory, &None);
+        assert_eq!(None, response_factory.response.headers.get("Server"));
+        add_server_header(&mut response_factory, &Some("arkos".into()));
+        add_server_header(&mut response_factory, &Some("other".into()));
+        assert_eq!(Some("arkos"), response_factory.response.headers.get("Server"));
+    }
 
-    Ok(())
 }
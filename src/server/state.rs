@@ -0,0 +1,36 @@
+use std::fmt;
+use std::ops::Deref;
+use std::sync::Arc;
+
+
+/// Shared state registered once on the Server with `Server::with_state`, as a database pool or the configuration.
+/// It is read in a Check or a Route Response with `request.state::<T>()`, and dereferences to `&T`.
+pub struct State<T>(pub(crate) Arc<T>);
+
+impl<T> State<T> {
+
+    /// Will return the Arc shared by every Request.
+    pub fn into_inner(self) -> Arc<T> {
+        self.0
+    }
+}
+
+impl<T> Deref for State<T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        &self.0
+    }
+}
+
+impl<T> Clone for State<T> {
+    fn clone(&self) -> Self {
+        State(Arc::clone(&self.0))
+    }
+}
+
+impl<T: fmt::Debug> fmt::Debug for State<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("State").field(&self.0).finish()
+    }
+}
@@ -0,0 +1,203 @@
+use std::fs;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::UNIX_EPOCH;
+
+use log::debug;
+
+use crate::core::{body::BodyFile, content::ContentType, date::HttpDate, etag::ETag, form::decode, method::HttpMethod, status::StatusCode};
+
+use super::{request::Request, response::Response, route::Route};
+
+
+/// Serves the files of a directory under a url prefix, as a compiled frontend next to the API.
+/// Files are sent with their Content-Type guessed from their extension, a Last-Modified, an ETag, and accept ranges.
+#[derive(Clone, Debug)]
+pub struct StaticFiles {
+    #[doc(hidden)]
+    pub prefix: String,
+    #[doc(hidden)]
+    pub root: PathBuf,
+    #[doc(hidden)]
+    pub index: Option<String>,
+}
+
+impl StaticFiles {
+
+    /// Will serve the directory `root` under the url `prefix`: `/assets/app.js` is read from `root/app.js` if the prefix is `/assets`.
+    /// Directories are served with their `index.html`.
+    pub fn new<P: Into<PathBuf>>(prefix: &str, root: P) -> Self {
+        StaticFiles { prefix: prefix.trim_end_matches('/').to_lowercase(), root: root.into(), index: Some("index.html".into()) }
+    }
+
+    /// Will replace the file served for a directory.
+    pub fn index(mut self, name: &str) -> Self {
+        self.index = Some(name.into());
+        self
+    }
+
+    /// Directories will receive a 404 Not Found.
+    pub fn without_index(mut self) -> Self {
+        self.index = None;
+        self
+    }
+
+    /// Will return the GET Route serving the files, on `prefix/**`. Checks and Checkpoints can be added to it as to any Route.
+    /// The prefix itself is redirected to `prefix/`, as the other directories.
+    pub fn route(&self) -> Route {
+        let mut route = Route::new(&format!("{}/**", self.prefix), HttpMethod::GET);
+        let files = self.clone();
+        route.set_response(Arc::new(move |req: Request| files.respond(&req)));
+        route
+    }
+
+    /// Will return the file targeted by the Request, a 404 Not Found if it is missing or outside the directory.
+    pub fn respond(&self, request: &Request) -> Result<Response, StatusCode> {
+        // The url is lowercased to find the Route, the path keeps the case of the file name.
+        let relative = request.path.get(self.prefix.len()..).ok_or(StatusCode::NotFound)?;
+        let mut file = match self.resolve(relative) {
+            Some(file) => file,
+            None => {
+                debug!("{} is not a file under {} - Returning 404 Not Found", request.path, self.root.display());
+                return Err(StatusCode::NotFound);
+            }
+        };
+        if file.is_dir() {
+            // Relative links of the index are resolved from the directory.
+            if !request.path.ends_with('/') {
+                return Err(StatusCode::MovedPermanently(directory_location(&request.path[..self.prefix.len()], relative, request.query.as_deref())));
+            }
+            file = file.join(self.index.as_ref().ok_or(StatusCode::NotFound)?);
+        }
+
+        let opened = fs::File::open(&file).map_err(|_| StatusCode::NotFound)?;
+        let metadata = opened.metadata().map_err(|_| StatusCode::InternalServerError)?;
+        if !metadata.is_file() {
+            return Err(StatusCode::NotFound);
+        }
+        let body = BodyFile::new(opened).map_err(|_| StatusCode::InternalServerError)?;
+        let modified = metadata.modified().map_err(|_| StatusCode::InternalServerError)?;
+        let secs = modified.duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or_default();
+
+        let mut response = Response::default();
+        let extension = file.extension().and_then(|e| e.to_str()).unwrap_or_default();
+        response.set_content_type(ContentType::from_extension(extension));
+        response.set_etag(&ETag::strong(&format!("{:x}-{:x}", secs, metadata.len())));
+        response.set_last_modified(HttpDate::from_unix(secs));
+        response.set_accept_ranges();
+        response.set_body_file(body);
+        Ok(response)
+    }
+
+    // Path of the file under the root. `..` is refused, and symbolic links are followed only if they stay inside the root.
+    fn resolve(&self, relative: &str) -> Option<PathBuf> {
+        let root = self.root.canonicalize().ok()?;
+        let mut file = root.clone();
+        for segment in relative.split('/') {
+            match decode(segment.as_bytes(), false).as_str() {
+                "" | "." => continue,
+                ".." => return None,
+                s if s.contains(['/', '\\', '\0']) => return None,
+                s => file.push(s),
+            }
+        }
+        let file = file.canonicalize().ok()?;
+        file.starts_with(&root).then_some(file)
+    }
+}
+
+// Location of the redirection to a directory: `//docs` becomes `/docs/`, not a url to the host `docs`. The query is kept.
+pub(crate) fn directory_location(prefix: &str, relative: &str, query: Option<&str>) -> String {
+    let mut location = prefix.to_string();
+    for segment in relative.split('/').filter(|s| !s.is_empty() && *s != ".") {
+        location.push('/');
+        location.push_str(segment);
+    }
+    location.push('/');
+    if let Some(query) = query {
+        location.push('?');
+        location.push_str(query);
+    }
+    location
+}
+
+
+#[cfg(test)]
+mod test {
+
+    use super::*;
+
+    // Directory removed when the test ends.
+    struct TempDir(PathBuf);
+
+    impl TempDir {
+        fn new(name: &str) -> Self {
+            let dir = std::env::temp_dir().join(format!("arkos-{}-{}", name, std::process::id()));
+            let _ = fs::remove_dir_all(&dir);
+            fs::create_dir_all(dir.join("public/docs")).unwrap();
+            fs::write(dir.join("public/App.js"), "console.log('arkos');").unwrap();
+            fs::write(dir.join("public/docs/index.html"), "<h1>Docs</h1>").unwrap();
+            fs::write(dir.join("secret.txt"), "secret").unwrap();
+            TempDir(dir)
+        }
+    }
+
+    impl Drop for TempDir {
+        fn drop(&mut self) {
+            let _ = fs::remove_dir_all(&self.0);
+        }
+    }
+
+    fn get(files: &StaticFiles, path: &str) -> Result<Response, StatusCode> {
+        let request = Request::parse(format!("GET {} HTTP/1.1\r\nHost: localhost\r\n\r\n", path)).unwrap();
+        files.respond(&request)
+    }
+
+    #[test]
+    fn serve_files() {
+        let dir = TempDir::new("serve");
+        let files = StaticFiles::new("/static/", dir.0.join("public"));
+        assert!(files.route().matches("/static/app.js"));
+        assert!(!files.route().matches("/staticfile"));
+
+        let response = get(&files, "/static/App.js").unwrap();
+        assert_eq!(ContentType::Custom("text/javascript; charset=utf-8".into()), response.content_type);
+        assert_eq!(b"console.log('arkos');".to_vec(), response.file.as_ref().unwrap().read_all().unwrap());
+        assert_eq!(Some("bytes"), response.headers.get("Accept-Ranges"));
+        assert!(response.etag().is_some() && response.last_modified().is_some());
+
+        assert_eq!(b"<h1>Docs</h1>".to_vec(), get(&files, "/static/docs/").unwrap().file.unwrap().read_all().unwrap());
+        assert_eq!(Err(StatusCode::MovedPermanently("/static/docs/".into())), get(&files, "/static/docs"));
+        assert_eq!(Err(StatusCode::MovedPermanently("/static/docs/?lang=fr".into())), get(&files, "/static//docs?lang=fr"));
+        assert!(files.route().matches("/static"));
+        assert_eq!(Err(StatusCode::MovedPermanently("/static/".into())), get(&files, "/static"));
+        assert_eq!(Err(StatusCode::NotFound), get(&files, "/static/"));
+        assert_eq!(Err(StatusCode::NotFound), get(&files, "/static/missing.js"));
+        assert_eq!(Err(StatusCode::NotFound), get(&files.without_index(), "/static/docs/"));
+    }
+
+    #[test]
+    fn mounted_at_root() {
+        let dir = TempDir::new("root");
+        let files = StaticFiles::new("/", dir.0.join("public"));
+        assert!(files.route().matches("/docs"));
+        assert_eq!(Err(StatusCode::MovedPermanently("/docs/".into())), get(&files, "//docs"));
+    }
+
+    #[test]
+    fn traversal_is_refused() {
+        let dir = TempDir::new("traversal");
+        let files = StaticFiles::new("/static", dir.0.join("public"));
+        assert_eq!(Err(StatusCode::NotFound), get(&files, "/static/../secret.txt"));
+        assert_eq!(Err(StatusCode::NotFound), get(&files, "/static/%2e%2e/secret.txt"));
+        assert_eq!(Err(StatusCode::NotFound), get(&files, "/static/docs%2f..%2f..%2fsecret.txt"));
+        #[cfg(unix)]
+        {
+            std::os::unix::fs::symlink(dir.0.join("secret.txt"), dir.0.join("public/escape.txt")).unwrap();
+            assert_eq!(Err(StatusCode::NotFound), get(&files, "/static/escape.txt"));
+            std::os::unix::fs::symlink(dir.0.join("public/App.js"), dir.0.join("public/inside.js")).unwrap();
+            assert!(get(&files, "/static/inside.js").is_ok());
+        }
+    }
+
+}
@@ -0,0 +1,45 @@
+// Base64 decoding of the standard and the URL-safe alphabets, with or without padding. (RFC 4648 - 4 & 5)
+pub fn decode(input: &str) -> Option<Vec<u8>> {
+    let input = input.trim_end_matches('=').as_bytes();
+    if input.len() % 4 == 1 {
+        return None;
+    }
+
+    let mut output = Vec::with_capacity(input.len() * 3 / 4);
+    let mut buffer: u32 = 0;
+    let mut bits = 0;
+    for c in input {
+        let value = match c {
+            b'A'..=b'Z' => c - b'A',
+            b'a'..=b'z' => c - b'a' + 26,
+            b'0'..=b'9' => c - b'0' + 52,
+            b'+' | b'-' => 62,
+            b'/' | b'_' => 63,
+            _ => return None,
+        };
+        buffer = (buffer << 6) | value as u32;
+        bits += 6;
+        if bits >= 8 {
+            bits -= 8;
+            output.push((buffer >> bits) as u8);
+        }
+    }
+    Some(output)
+}
+
+
+#[cfg(test)]
+mod test {
+
+    use super::*;
+
+    #[test]
+    fn alphabets_and_padding() {
+        assert_eq!(Some(b"Aladdin:open sesame".to_vec()), decode("QWxhZGRpbjpvcGVuIHNlc2FtZQ=="));
+        assert_eq!(Some(vec![0xfb, 0xff]), decode("+/8="));
+        assert_eq!(Some(vec![0xfb, 0xff]), decode("-_8"));
+        assert_eq!(None, decode("QWxhZ"));
+        assert_eq!(None, decode("QW*h"));
+    }
+
+}
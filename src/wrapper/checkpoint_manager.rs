@@ -19,22 +19,22 @@ impl CheckpointManager{
 
 
     // CheckMethod
-    pub fn verify(&self, req: Request) -> Option<StatusCode> {
-        if self.checkpoint.paths.len() == 0 {
+    pub fn verify(&self, req: &mut Request) -> Option<StatusCode> {
+        if self.checkpoint.paths.is_empty() {
             warn!("Checkpoint has been declared, but no path has been found.");
             return None;
         }
 
         for path in &self.checkpoint.except { // Iterate in all paths declared as exception - Return None as it is excempted if true.
-            if compare(&path, &req.url) {
+            if compare(path, &req.url) {
                 return None;
             }
         }
 
 
         for path in &self.checkpoint.paths { // Iterate in all paths declared to be checked
-            if compare(&path, &req.url) {
-                match (&self.checkpoint.check)(req.clone()) {
+            if compare(path, &req.url) {
+                match (self.checkpoint.check)(req) {
                     Ok(_) => continue,
                     Err(e) => return Some(e),
                 }
@@ -47,13 +47,8 @@ impl CheckpointManager{
 
 pub fn compare(list: &str, req: &str) -> bool {
 
-    // Exact pattern /
-    if list.eq(req) {
-        return true;
-    }
-
-    // Subpath allowed /* 
-    else if list.ends_with("**") && req.starts_with(list.strip_suffix("**").unwrap()) {
+    // Exact pattern / - Subpath allowed /**
+    if list.eq(req) || (list.ends_with("**") && req.starts_with(list.strip_suffix("**").unwrap())) {
         return true;
     }
 
@@ -61,8 +56,7 @@ pub fn compare(list: &str, req: &str) -> bool {
     else if list.ends_with("*") && req.starts_with(list.strip_suffix("*").unwrap()) {
         
         // list : /hello/*  req: /hello/hello
-        if req.ends_with('/') {
-            let req = req.strip_suffix('/').unwrap();
+        if let Some(req) = req.strip_suffix('/') {
             return list.matches('/').count() == req.matches('/').count();
         }
 
@@ -78,7 +72,7 @@ mod test {
     
     use std::{sync::Arc, collections::HashMap};
 
-    use crate::{core::method::HttpMethod};
+    use crate::core::{method::HttpMethod, header::HeaderMap, extensions::Extensions};
 
     use super::*;
     
@@ -86,12 +80,12 @@ mod test {
     // Send - Path in Checkpoint - Expected
 
     fn base(list: &str) -> CheckpointManager {
-        let checkpoint = Checkpoint::new(vec!(list.into(), "/".into()), Arc::new(|_req: Request| {Err(StatusCode::BadRequest)} ));
+        let checkpoint = Checkpoint::new(vec!(list.into(), "/".into()), Arc::new(|_req: &mut Request| {Err(StatusCode::BadRequest)} ));
         CheckpointManager::new(checkpoint)
     }
 
     fn base_req(path: &str ) -> Request {
-        Request { method: HttpMethod::GET, url: path.into(), headers: HashMap::new(), cookies: HashMap::new(), param: HashMap::new(), body: "".into() }
+        Request { method: HttpMethod::GET, url: path.into(), path: path.into(), headers: HeaderMap::new(), cookies: HashMap::new(), param: HashMap::new(), query: None, body: "".into(), extensions: Extensions::new(), states: Arc::new(Extensions::new()) }
     }
 
 
@@ -106,8 +100,8 @@ mod test {
     fn except_exact() {
         let mut m = base("/hello");
         m.checkpoint.except("/hello");
-        let req = base_req("/hello");
-        assert_eq!(None, m.verify(req));
+        let mut req = base_req("/hello");
+        assert_eq!(None, m.verify(&mut req));
     }
 
     // /hello - /** but /hello exempted -> Some() as it doesn't match the exact pattern
@@ -115,8 +109,8 @@ mod test {
     fn except_exact_incomplete_path() {
         let mut m = base("/**");
         m.checkpoint.except("/hello");
-        let req = base_req("/hell");
-        assert_eq!(Some(StatusCode::BadRequest), m.verify(req));
+        let mut req = base_req("/hell");
+        assert_eq!(Some(StatusCode::BadRequest), m.verify(&mut req));
     }
 
 
@@ -126,56 +120,56 @@ mod test {
     #[test]
     fn exact() {
         let m = base("/hello");
-        let req = base_req("/hello");
-        assert_eq!(Some(StatusCode::BadRequest), m.verify(req));
+        let mut req = base_req("/hello");
+        assert_eq!(Some(StatusCode::BadRequest), m.verify(&mut req));
     }
 
     // /hello/hello - /hello -> None
     #[test]
     fn subpath_sended_too_much() {
         let m = base("/hello");
-        let req = base_req("/hello/hello");
-        assert_eq!(None, m.verify(req));
+        let mut req = base_req("/hello/hello");
+        assert_eq!(None, m.verify(&mut req));
     }
 
     // /hello/hello - /hello/hello -> Some
     #[test]
     fn subpath_sended_exact() {
         let m = base("/hello/hello");
-        let req = base_req("/hello/hello");
-        assert_eq!(Some(StatusCode::BadRequest), m.verify(req));
+        let mut req = base_req("/hello/hello");
+        assert_eq!(Some(StatusCode::BadRequest), m.verify(&mut req));
     }
 
     // /hello/hello - /hello/* -> Some
     #[test]
     fn subpath_sended_subpath_allowed() {
         let m = base("/hello/*");
-        let req = base_req("/hello/hello/");
-        assert_eq!(Some(StatusCode::BadRequest), m.verify(req));
+        let mut req = base_req("/hello/hello/");
+        assert_eq!(Some(StatusCode::BadRequest), m.verify(&mut req));
     }
 
     // /hello/hello - /hello/** -> Some
     #[test]
     fn subpath_sended_is_base() {
         let m = base("/hello/**");
-        let req = base_req("/hello/hello");
-        assert_eq!(Some(StatusCode::BadRequest), m.verify(req));
+        let mut req = base_req("/hello/hello");
+        assert_eq!(Some(StatusCode::BadRequest), m.verify(&mut req));
     }
 
     // /hello/hello/hello - /hello/* -> None
     #[test]
     fn subsubpath_sended_too_much() {
         let m = base("/hello/*");
-        let req = base_req("/hello/hello/hello");
-        assert_eq!(None, m.verify(req));
+        let mut req = base_req("/hello/hello/hello");
+        assert_eq!(None, m.verify(&mut req));
     }
 
     // /hello/hello/hello - /hello/** -> Some
     #[test]
     fn subsubpath_sended_base_allowed() {
         let m = base("/hello/**");
-        let req = base_req("/hello/hello/hello");
-        assert_eq!(Some(StatusCode::BadRequest), m.verify(req));
+        let mut req = base_req("/hello/hello/hello");
+        assert_eq!(Some(StatusCode::BadRequest), m.verify(&mut req));
     }
 
 }
\ No newline at end of file
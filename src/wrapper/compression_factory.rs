@@ -0,0 +1,167 @@
+use std::io::Read;
+
+use log::{debug, trace, warn};
+
+use crate::core::{compression::{is_compressible, Compression, Encoding}, etag::ETag, header::HeaderMap, negotiation::negotiate_encoding, status::StatusCode};
+use crate::server::response::Response;
+
+
+// Will compress the body of a 200 Ok Response with the content coding the client prefers, according to its Accept-Encoding.
+// Streamed and file bodies, bodies already encoded, too small or not compressible are sent as they are.
+pub fn compress(headers: &HeaderMap, response: &mut Response, compression: &Compression) {
+    if !compression.is_enabled() || response.status != StatusCode::Ok || response.stream.is_some() || response.file.is_some()
+        || response.headers.contains_key("Content-Encoding") || response.body.len() < compression.min_size
+        || !is_compressible(&response.content_type.get()) {
+        return;
+    }
+    // Compressed or not, the representation sent depends on the Accept-Encoding of the Request.
+    if !response.headers.get_list("Vary").iter().any(|v| v.eq_ignore_ascii_case("Accept-Encoding") || *v == "*") {
+        response.headers.append("Vary", "Accept-Encoding");
+    }
+
+    // Without Accept-Encoding, any coding would be acceptable: the body is kept as it is for clients that don't decode. (RFC 9110 - 12.5.3)
+    let accept_encoding = headers.get_all("Accept-Encoding");
+    if accept_encoding.is_empty() {
+        return;
+    }
+    let mut offered: Vec<&str> = compression.encodings.iter().map(Encoding::name).collect();
+    offered.push("identity");
+    let encoding = match negotiate_encoding(&accept_encoding, &offered).as_deref().and_then(Encoding::from_name) {
+        Some(encoding) => encoding,
+        None => return,
+    };
+
+    match encoding.encode(&response.body) {
+        Ok(body) => {
+            debug!("Body compressed with {} from {} to {} bytes.", encoding.name(), response.body.len(), body.len());
+            response.body = body;
+            response.set_header("Content-Encoding", encoding.name());
+            // The compressed body is not byte-for-byte the one the ETag was computed for. (RFC 9110 - 8.8.3)
+            if let Some(etag) = response.etag().filter(|e| !e.weak) {
+                response.set_etag(&ETag::weak(&etag.tag));
+            }
+        }
+        Err(e) => warn!("Failed to compress a body with {}: {} - Sending it uncompressed.", encoding.name(), e),
+    }
+}
+
+// Value of the Accept-Encoding header sent with the 415 Unsupported Media Type of a body encoded with an unsupported coding. (RFC 9110 - 15.5.16)
+pub fn accepted_encodings() -> String {
+    let mut accepted: Vec<&str> = Encoding::available().iter().map(Encoding::name).collect();
+    accepted.push("identity");
+    accepted.join(", ")
+}
+
+// Will decompress a body sent with a Content-Encoding, the last coding applied being undone first. (RFC 9110 - 8.4)
+// A coding unknown or whose feature is not enabled returns a 415 Unsupported Media Type,
+// a body larger than `limit` once decompressed a 413 Payload Too Large, and a corrupted body a 400 Bad Request.
+pub fn decompress(content_encoding: &[&str], body: Vec<u8>, limit: usize) -> Result<Vec<u8>, StatusCode> {
+    let mut body = body;
+    for coding in content_encoding.iter().rev().filter(|c| !c.eq_ignore_ascii_case("identity")) {
+        let decoder = match Encoding::from_name(coding).map(|e| e.decoder(&body)) {
+            Some(Ok(decoder)) => decoder,
+            _ => {
+                trace!("The body is encoded with {}, which is not supported - Returning 415 Unsupported Media Type", coding);
+                return Err(StatusCode::UnsupportedMediaType);
+            }
+        };
+        // One more byte read tells the limit has been exceeded, without decompressing a zip bomb whole.
+        let mut decoded = Vec::new();
+        decoder.take(limit as u64 + 1).read_to_end(&mut decoded).map_err(|e| {
+            trace!("The body could not be decompressed with {}: {} - Returning 400 Bad Request", coding, e);
+            StatusCode::BadRequest
+        })?;
+        if decoded.len() > limit {
+            trace!("The body is larger than {} bytes once decompressed - Returning 413 Payload Too Large", limit);
+            return Err(StatusCode::PayloadTooLarge);
+        }
+        body = decoded;
+    }
+    Ok(body)
+}
+
+
+#[cfg(all(test, feature = "gzip", feature = "brotli"))]
+mod test {
+
+    use super::*;
+    use crate::core::content::ContentType;
+
+    fn response(size: usize) -> Response {
+        let mut response = Response::default();
+        response.set_body("a".repeat(size));
+        response.set_etag(&ETag::strong("v1"));
+        response
+    }
+
+    fn headers(accept_encoding: &str) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        headers.append("Accept-Encoding", accept_encoding);
+        headers
+    }
+
+    #[test]
+    fn negotiated_coding() {
+        let compression = Compression::new().encodings(&[Encoding::Brotli, Encoding::Gzip]);
+        let mut compressed = response(2000);
+        compress(&headers("gzip, br;q=0.8"), &mut compressed, &compression);
+        assert_eq!(Some("gzip"), compressed.headers.get("Content-Encoding"));
+        assert_eq!(Some("Accept-Encoding"), compressed.headers.get("Vary"));
+        assert_eq!(Some(ETag::weak("v1")), compressed.etag());
+        assert!(compressed.body.len() < 2000);
+
+        let mut compressed = response(2000);
+        compress(&headers("*"), &mut compressed, &compression);
+        assert_eq!(Some("br"), compressed.headers.get("Content-Encoding"));
+
+        let mut identity = response(2000);
+        compress(&headers("gzip;q=0.5, identity"), &mut identity, &compression);
+        assert_eq!(None, identity.headers.get("Content-Encoding"));
+        assert_eq!(Some("Accept-Encoding"), identity.headers.get("Vary"));
+        assert_eq!(2000, identity.body.len());
+    }
+
+    #[test]
+    fn skipped_bodies() {
+        let compression = Compression::new().min_size(1024);
+        let mut small = response(100);
+        compress(&headers("gzip"), &mut small, &compression);
+        assert_eq!(None, small.headers.get("Content-Encoding"));
+
+        let mut image = response(2000);
+        image.set_content_type(ContentType::Custom("image/png".into()));
+        compress(&headers("gzip"), &mut image, &compression);
+        assert_eq!(None, image.headers.get("Content-Encoding"));
+        assert_eq!(None, image.headers.get("Vary"));
+
+        let mut no_header = response(2000);
+        compress(&HeaderMap::new(), &mut no_header, &compression);
+        assert_eq!(None, no_header.headers.get("Content-Encoding"));
+
+        let mut disabled = response(2000);
+        compress(&headers("gzip"), &mut disabled, &Compression::disabled());
+        assert_eq!(2000, disabled.body.len());
+    }
+
+    #[test]
+    fn decompressed_bodies() {
+        let body = b"{\"name\": \"arkos\"}".repeat(100);
+        let gzip = Encoding::Gzip.encode(&body).unwrap();
+        assert_eq!(Ok(body.clone()), decompress(&["gzip"], gzip.clone(), 10_000));
+        assert_eq!(Ok(body.clone()), decompress(&["identity", "gzip"], gzip.clone(), 10_000));
+        let twice = Encoding::Brotli.encode(&gzip).unwrap();
+        assert_eq!(Ok(body.clone()), decompress(&["gzip", "br"], twice, 10_000));
+
+        assert_eq!(Err(StatusCode::PayloadTooLarge), decompress(&["gzip"], gzip.clone(), 1_000));
+        assert_eq!(Err(StatusCode::UnsupportedMediaType), decompress(&["compress"], gzip.clone(), 10_000));
+        assert_eq!(Err(StatusCode::BadRequest), decompress(&["gzip"], body.clone(), 10_000));
+    }
+
+    #[test]
+    fn zip_bomb_is_stopped() {
+        let bomb = Encoding::Gzip.encode(&vec![0u8; 8 * 1024 * 1024]).unwrap();
+        assert!(bomb.len() < 20_000);
+        assert_eq!(Err(StatusCode::PayloadTooLarge), decompress(&["gzip"], bomb, 1024 * 1024));
+    }
+
+}
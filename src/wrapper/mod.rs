@@ -1,4 +1,7 @@
 pub mod checkpoint_manager;
 pub mod request_factory;
 pub mod cookie_factory;
-pub mod response_factory;
\ No newline at end of file
+pub mod response_factory;
+pub mod base64;
+pub mod range_factory;
+pub mod compression_factory;
\ No newline at end of file
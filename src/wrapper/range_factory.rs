@@ -0,0 +1,182 @@
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use log::debug;
+
+use crate::core::{content::ContentType, date::HttpDate, etag::ETag, header::HeaderMap, range::{content_range, resolve_ranges, ByteRangeSpec}, status::StatusCode};
+use crate::server::response::Response;
+
+
+// Beyond, the Range is ignored and the full representation is sent.
+const MAX_RANGES: usize = 32;
+
+
+// Will turn a 200 Ok Response accepting ranges into a 206 Partial Content or a 416 Range Not Satisfiable
+// if the Request asks for a Range, and its If-Range, if any, matches the representation. (RFC 9110 - 14)
+pub fn apply_range(headers: &HeaderMap, response: &mut Response) {
+    let specs = match headers.get("Range").and_then(ByteRangeSpec::parse) {
+        Some(specs) => specs,
+        None => return,
+    };
+    if let Some(condition) = headers.get("If-Range") {
+        if !if_range_matches(condition, response) {
+            debug!("If-Range doesn't match the representation - Sending it whole.");
+            return;
+        }
+    }
+
+    let length = match &response.file {
+        Some(file) => file.len(),
+        None => response.body.len() as u64,
+    };
+    let ranges = resolve_ranges(&specs, length);
+    match ranges.len() {
+        0 => {
+            response.status = StatusCode::RangeNotSatisfiable(Some(length));
+            response.body.clear();
+            response.file = None;
+        }
+        1 => {
+            let range = &ranges[0];
+            response.status = StatusCode::PartialContent;
+            response.set_header("Content-Range", &content_range(range, length));
+            match &response.file {
+                Some(file) => response.file = Some(file.slice(range.clone())),
+                None => response.body = response.body[range.start as usize..range.end as usize].to_vec(),
+            }
+        }
+        n if n > MAX_RANGES => debug!("{} ranges asked - Sending the representation whole.", n),
+        _ => {
+            let parts: Vec<Vec<u8>> = match &response.file {
+                Some(file) => match ranges.iter().map(|r| file.slice(r.clone()).read_all()).collect() {
+                    Ok(parts) => parts,
+                    Err(e) => {
+                        debug!("Failed to read the ranges of the file: {} - Sending the representation whole.", e);
+                        return;
+                    }
+                },
+                None => ranges.iter().map(|r| response.body[r.start as usize..r.end as usize].to_vec()).collect(),
+            };
+            let boundary = boundary(&parts.concat());
+            let part_type = response.content_type.get();
+            let mut body = Vec::new();
+            for (range, part) in ranges.iter().zip(&parts) {
+                body.extend_from_slice(format!("\r\n--{}\r\nContent-Type: {}\r\nContent-Range: {}\r\n\r\n", boundary, part_type, content_range(range, length)).as_bytes());
+                body.extend_from_slice(part);
+            }
+            body.extend_from_slice(format!("\r\n--{}--\r\n", boundary).as_bytes());
+            response.status = StatusCode::PartialContent;
+            response.set_content_type(ContentType::Custom(format!("multipart/byteranges; boundary={}", boundary)));
+            response.file = None;
+            response.body = body;
+        }
+    }
+}
+
+// If-Range holds an ETag compared strongly, or the exact Last-Modified date. (RFC 9110 - 13.1.5)
+fn if_range_matches(condition: &str, response: &Response) -> bool {
+    match ETag::parse(condition) {
+        Some(etag) => response.etag().is_some_and(|current| current.strong_eq(&etag)),
+        None => HttpDate::parse(condition).is_some_and(|date| response.last_modified() == Some(date)),
+    }
+}
+
+// Boundary that cannot be found in the body.
+fn boundary(body: &[u8]) -> String {
+    let nanos = SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.subsec_nanos()).unwrap_or_default();
+    let mut seed = nanos as u64;
+    loop {
+        let boundary = format!("arkos-{}", ETag::from_bytes(&seed.to_le_bytes()).tag);
+        if !body.windows(boundary.len()).any(|w| w == boundary.as_bytes()) {
+            return boundary;
+        }
+        seed += 1;
+    }
+}
+
+
+#[cfg(test)]
+mod test {
+
+    use super::*;
+    use crate::core::body::BodyFile;
+    use crate::core::status::HttpStatusCode;
+
+    fn representation() -> Response {
+        let mut response = Response::default();
+        response.set_content_type(ContentType::Text);
+        response.set_body("0123456789".into());
+        response.set_etag(&ETag::strong("v1"));
+        response
+    }
+
+    fn headers(range: &str) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        headers.append("Range", range);
+        headers
+    }
+
+    #[test]
+    fn single_range() {
+        let mut response = representation();
+        apply_range(&headers("bytes=2-4"), &mut response);
+        assert_eq!(StatusCode::PartialContent, response.status);
+        assert_eq!(Some("bytes 2-4/10"), response.headers.get("Content-Range"));
+        assert_eq!(Some("234"), response.text());
+    }
+
+    #[test]
+    fn multiple_ranges() {
+        let mut response = representation();
+        apply_range(&headers("bytes=0-1,-2"), &mut response);
+        assert_eq!(StatusCode::PartialContent, response.status);
+        let content_type = response.content_type.get();
+        let boundary = content_type.strip_prefix("multipart/byteranges; boundary=").unwrap();
+        assert_eq!(Some(format!("\r\n--{b}\r\nContent-Type: text/plain\r\nContent-Range: bytes 0-1/10\r\n\r\n01\r\n--{b}\r\nContent-Type: text/plain\r\nContent-Range: bytes 8-9/10\r\n\r\n89\r\n--{b}--\r\n", b = boundary).as_str()), response.text());
+    }
+
+    #[test]
+    fn not_satisfiable() {
+        let mut response = representation();
+        apply_range(&headers("bytes=10-"), &mut response);
+        assert_eq!(StatusCode::RangeNotSatisfiable(Some(10)), response.status);
+        assert_eq!("Content-Range: bytes */10\r\n", response.status.generate_headers());
+        assert!(response.body.is_empty());
+    }
+
+    #[test]
+    fn file_ranges() {
+        let path = std::env::temp_dir().join(format!("arkos-range-{}", std::process::id()));
+        std::fs::write(&path, "0123456789").unwrap();
+        let mut file = representation();
+        file.set_body_file(BodyFile::open(&path).unwrap());
+        std::fs::remove_file(&path).unwrap();
+
+        let mut response = file.clone();
+        apply_range(&headers("bytes=-3"), &mut response);
+        assert_eq!(Some("bytes 7-9/10"), response.headers.get("Content-Range"));
+        assert_eq!(b"789".to_vec(), response.file.unwrap().read_all().unwrap());
+
+        let mut response = file;
+        apply_range(&headers("bytes=0-0,9-"), &mut response);
+        assert_eq!(StatusCode::PartialContent, response.status);
+        assert!(response.file.is_none());
+        assert!(response.text().unwrap().contains("Content-Range: bytes 9-9/10\r\n\r\n9\r\n"));
+    }
+
+    #[test]
+    fn if_range() {
+        let mut matching = headers("bytes=0-0");
+        matching.append("If-Range", "\"v1\"");
+        let mut response = representation();
+        apply_range(&matching, &mut response);
+        assert_eq!(StatusCode::PartialContent, response.status);
+
+        let mut outdated = headers("bytes=0-0");
+        outdated.append("If-Range", "\"v0\"");
+        let mut response = representation();
+        apply_range(&outdated, &mut response);
+        assert_eq!(StatusCode::Ok, response.status);
+        assert_eq!(Some("0123456789"), response.text());
+    }
+
+}
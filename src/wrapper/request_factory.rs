@@ -1,74 +1,416 @@
 use log::trace;
 
-use crate::{core::{status::StatusCode, method::HttpMethod}, server::request::Request};
+use crate::{core::{status::StatusCode, method::HttpMethod, header::HeaderMap, form::decode, extensions::Extensions}, server::request::Request};
+use super::compression_factory::decompress;
+use std::borrow::Cow;
 use std::collections::HashMap;
+use std::io::Read;
+use std::sync::Arc;
 
 
+/// Longest request-target accepted before answering 414 URI Too Long.
+pub const MAX_URI_LENGTH: usize = 8 * 1024;
+/// Largest header section (every field line after the request-line) accepted before answering 431.
+pub const MAX_HEADER_SECTION_SIZE: usize = 16 * 1024;
+/// Maximum number of field lines accepted before answering 431.
+pub const MAX_HEADER_COUNT: usize = 100;
+/// Largest body read from the stream before answering 413 Payload Too Large.
+pub const MAX_BODY_SIZE: usize = 10 * 1024 * 1024;
+/// Largest body once decompressed, according to its Content-Encoding, before answering 413 Payload Too Large.
+pub const MAX_DECODED_BODY_SIZE: usize = 10 * 1024 * 1024;
 
-// Parse a Request from a HTTP 1.X Request
-// TODO: Make it code compliant
 
-pub fn parse_http1x(raw_request: &str, _subversion: &u8) -> Result<Request, StatusCode> {
-    // Firs thing we do is check to see if we have the `body` separator: \r\n\r\n
-    // and if we do, we take all the bytes up until the body separator.
-    //
-    // We also keep `pos` around so we can get the `body` at the end, by starting
-    // from the `pos` and taking the remaining bytes
-    let pos = raw_request.find("\r\n\r\n").unwrap_or(raw_request.len());
+// Read a whole HTTP/1.X message from the stream: the head up to the empty line, then as many
+// bytes as announced by the Content-Length header.
+// The outer Result is for IO failures, the inner one for requests that must be refused before being parsed.
+pub fn read_http1x<R: Read>(stream: &mut R) -> std::io::Result<Result<Vec<u8>, StatusCode>> {
+    let mut raw: Vec<u8> = Vec::new();
+    let mut buffer = [0; 4096];
 
-    // Create a line iterator, meaning for every iteration we are looking at a new line
-    let mut parsed = raw_request[..pos].lines();
+    // 1. Read until the end of the head.
+    let head_end = loop {
+        if let Some(pos) = find(&raw, b"\r\n\r\n") {
+            break pos;
+        }
 
-    // Get the method and path iterator
-    let mut method_and_path = parsed.next().map(|line| line.split(' ')).ok_or(StatusCode::InternalServerError)?;
-    // ... and then get the method
-    let method = HttpMethod::from_str(method_and_path.next().ok_or(StatusCode::InternalServerError)?);
-    // ... and finally the path
-    let mut url_and_param = method_and_path.next().map(|line| line.split('?')).ok_or(StatusCode::InternalServerError)?;
+        let line_end = find(&raw, b"\r\n");
+        if line_end.is_none() && raw.len() > MAX_URI_LENGTH + 64 {
+            trace!("Request-line is longer than {} bytes - Returning 414 URI Too Long", MAX_URI_LENGTH);
+            return Ok(Err(StatusCode::URIToolLong));
+        }
+        if raw.len() > line_end.unwrap_or(0) + MAX_HEADER_SECTION_SIZE {
+            trace!("Header section is larger than {} bytes - Returning 431 Request Header Fields Too Large", MAX_HEADER_SECTION_SIZE);
+            return Ok(Err(StatusCode::RequestHeaderFieldsTooLarge));
+        }
 
-    let url = url_and_param.next().ok_or(StatusCode::InternalServerError)?;
-    let url = url.to_lowercase();
+        let bytes_read = stream.read(&mut buffer)?;
+        if bytes_read == 0 {
+            if raw.is_empty() {
+                return Err(std::io::ErrorKind::UnexpectedEof.into());
+            }
+            // Incomplete head: the parser will refuse it.
+            return Ok(Ok(raw));
+        }
+        raw.extend_from_slice(&buffer[..bytes_read]);
+    };
 
-    let params : HashMap<String, String> = match url_and_param.next() {
-        Some(param_line) => {
-            let mut params = HashMap::new();
-            for mut param in param_line.split('&').map(|c| c.splitn(2, '=')) {
-                if let (Some(key), Some(value)) = (param.next(), param.next()) {
-                    params.insert(key.into(), value.into());
-                }
-            };
-            params
-        },
-        None => HashMap::new(),
+    // 2. Read the body announced by Content-Length, if any. Its validity is checked by the parser.
+    let content_length = String::from_utf8_lossy(&raw[..head_end])
+        .split("\r\n")
+        .filter_map(|line| line.split_once(':'))
+        .find(|(name, _)| name.eq_ignore_ascii_case("content-length"))
+        .and_then(|(_, value)| content_length_value(value).ok())
+        .unwrap_or(0);
+
+    if content_length > MAX_BODY_SIZE {
+        trace!("Content-Length of {} is larger than {} bytes - Returning 413 Payload Too Large", content_length, MAX_BODY_SIZE);
+        return Ok(Err(StatusCode::PayloadTooLarge));
+    }
+
+    let expected = head_end + 4 + content_length;
+    while raw.len() < expected {
+        let bytes_read = stream.read(&mut buffer)?;
+        if bytes_read == 0 {
+            break;
+        }
+        raw.extend_from_slice(&buffer[..bytes_read]);
+    }
+    raw.truncate(expected.max(head_end + 4));
+
+    Ok(Ok(raw))
+}
+
+// Return the request-line, skipping the empty lines that may precede it. Used to know the Protocol before parsing.
+pub fn request_line(raw_request: &[u8]) -> Cow<'_, str> {
+    let line = raw_request.split(|b| *b == b'\n').find(|line| !line.is_empty() && *line != b"\r").unwrap_or_default();
+    String::from_utf8_lossy(line.strip_suffix(b"\r").unwrap_or(line))
+}
+
+fn find(haystack: &[u8], needle: &[u8]) -> Option<usize> {
+    haystack.windows(needle.len()).position(|w| w == needle)
+}
+
+
+// Parse a Request from a HTTP 1.X Request, following RFC 9112.
+// Malformed messages are refused with 400 Bad Request, an oversized request-target with 414 URI Too Long and
+// an oversized header section with 431 Request Header Fields Too Large.
+pub fn parse_http1x(raw_request: &[u8], subversion: &u8) -> Result<Request, StatusCode> {
+    // A server SHOULD ignore at least one empty line received prior to the request-line. (RFC 9112 - 2.2)
+    let mut raw_request = raw_request;
+    while let Some(rest) = raw_request.strip_prefix(b"\r\n") {
+        raw_request = rest;
+    }
+
+    // The head ends with an empty line, everything after is the body and is kept as bytes.
+    let pos = find(raw_request, b"\r\n\r\n").ok_or_else(|| refuse("the head is not terminated by an empty line"))?;
+    let head = String::from_utf8_lossy(&raw_request[..pos]);
+    let remaining = &raw_request[pos + 4..];
+
+    let mut lines = head.split("\r\n");
+    if lines.clone().any(|line| line.contains('\r') || line.contains('\n')) {
+        return Err(refuse("a line is not terminated by CRLF"));
+    }
+
+    // 1. Request-line: method SP request-target SP HTTP-version
+    let request_line = lines.next().unwrap_or_default();
+    let mut parts = request_line.split(' ');
+    let (method, target, version) = match (parts.next(), parts.next(), parts.next(), parts.next()) {
+        (Some(m), Some(t), Some(v), None) => (m, t, v),
+        _ => return Err(refuse("the request-line is not made of three elements separated by a single space")),
     };
-    
-    
 
+    if !is_token(method) {
+        return Err(refuse("the method is not a token"));
+    }
+    let method = match HttpMethod::parse(method) {
+        Some(m) => m,
+        None => {
+            trace!("Method {} is not implemented - Returning 501 Not Implemented", method);
+            return Err(StatusCode::NotImplemented);
+        }
+    };
 
-    let mut headers: HashMap<String, String> = HashMap::new();
+    if target.len() > MAX_URI_LENGTH {
+        trace!("Request-target is longer than {} bytes - Returning 414 URI Too Long", MAX_URI_LENGTH);
+        return Err(StatusCode::URIToolLong);
+    }
+    if target.is_empty() || !target.bytes().all(|b| b.is_ascii_graphic()) || target.contains('#') {
+        return Err(refuse("the request-target contains forbidden characters"));
+    }
+
+    let version = version.as_bytes();
+    if version.len() != 8 || !version.starts_with(b"HTTP/") || !version[5].is_ascii_digit() || version[6] != b'.' || !version[7].is_ascii_digit() {
+        return Err(refuse("the HTTP-version is malformed"));
+    }
+
+    // 2. Request-target: origin-form, absolute-form or asterisk-form.
+    let path_and_query = if target.starts_with('/') || (target == "*" && method == HttpMethod::OPTIONS) {
+        target
+    } else if let Some(rest) = target.strip_prefix("http://").or_else(|| target.strip_prefix("https://")) {
+        rest.find('/').map(|i| &rest[i..]).unwrap_or("/")
+    } else {
+        return Err(refuse("the request-target is not in origin, absolute or asterisk form"));
+    };
+
+    let mut url_and_param = path_and_query.splitn(2, '?');
+    let path = url_and_param.next().unwrap_or_default().to_string();
+    let url = path.to_lowercase();
+
+    let query = url_and_param.next().map(String::from);
+    let mut params: HashMap<String, String> = HashMap::new();
+    if let Some(param_line) = &query {
+        for param in param_line.split('&') {
+            if let Some((key, value)) = param.split_once('=') {
+                params.insert(decode(key.as_bytes(), true), decode(value.as_bytes(), true));
+            }
+        }
+    }
+
+    // 3. Header section
+    if head.len() - request_line.len() > MAX_HEADER_SECTION_SIZE {
+        trace!("Header section is larger than {} bytes - Returning 431 Request Header Fields Too Large", MAX_HEADER_SECTION_SIZE);
+        return Err(StatusCode::RequestHeaderFieldsTooLarge);
+    }
+
+    let mut headers = HeaderMap::new();
     let mut cookies: HashMap<String, String> = HashMap::new();
+    let mut content_length: Option<usize> = None;
+    let mut transfer_encoding = false;
+    let mut host_count = 0;
 
-    // Cookie: yummy_cookie=choco; tasty_cookie=strawberry;
-    for row in parsed {
-        match row.strip_prefix("Cookie: ") {
-            Some(cookie_data) => {
-                for mut data in cookie_data.split(';').map(|c| c.splitn(2, '=')) {
-                    if let (Some(key), Some(val)) = (data.next(), data.next()) {
-                        cookies.insert(key.into(), val.into());
-                    }
-                }
+    for (count, row) in lines.enumerate() {
+        if count >= MAX_HEADER_COUNT {
+            trace!("More than {} header fields - Returning 431 Request Header Fields Too Large", MAX_HEADER_COUNT);
+            return Err(StatusCode::RequestHeaderFieldsTooLarge);
+        }
+
+        // obs-fold, or whitespace between the request-line and the first field line. (RFC 9112 - 2.2 & 5.2)
+        if row.starts_with(' ') || row.starts_with('\t') {
+            return Err(refuse("a field line starts with whitespace"));
+        }
+
+        let (name, value) = row.split_once(':').ok_or_else(|| refuse("a field line has no colon"))?;
+        // No whitespace is allowed between the field name and colon. (RFC 9112 - 5.1)
+        if !is_token(name) {
+            return Err(refuse("a field name is not a token"));
+        }
+        let value = value.trim_matches(|c| c == ' ' || c == '\t');
+        if value.chars().any(|c| c.is_ascii_control() && c != '\t') {
+            return Err(refuse("a field value contains control characters"));
+        }
+
+        if name.eq_ignore_ascii_case("content-length") {
+            let length = content_length_value(value).map_err(refuse)?;
+            if content_length.is_some_and(|l| l != length) {
+                return Err(refuse("multiple Content-Length with different values"));
             }
-            None => {
-                let mut header_data = row.split(":");
-                if let (Some(name), Some(value)) = (header_data.next(), header_data.next()) {
-                    headers.insert(name.into(), value.into());
+            content_length = Some(length);
+        } else if name.eq_ignore_ascii_case("transfer-encoding") {
+            transfer_encoding = true;
+        } else if name.eq_ignore_ascii_case("host") {
+            host_count += 1;
+        } else if name.eq_ignore_ascii_case("cookie") {
+            // Cookie: yummy_cookie=choco; tasty_cookie=strawberry
+            for data in value.split(';') {
+                if let Some((key, val)) = data.split_once('=') {
+                    cookies.insert(key.trim().into(), val.trim().into());
                 }
             }
         }
+
+        headers.append(name, value);
+    }
+
+    // A server MUST respond with a 400 to any HTTP/1.1 request that lacks a Host header or contains more than one. (RFC 9112 - 3.2)
+    if *subversion >= 1 && host_count != 1 {
+        return Err(refuse("a HTTP/1.1 request must contain exactly one Host header"));
+    }
+
+    if transfer_encoding {
+        if content_length.is_some() {
+            return Err(refuse("both Transfer-Encoding and Content-Length are present"));
+        }
+        trace!("Transfer-Encoding is not supported for requests - Returning 501 Not Implemented");
+        return Err(StatusCode::NotImplemented);
+    }
+
+    // 4. Body: without Content-Length, a request has no body. (RFC 9112 - 6.3)
+    let body = match content_length {
+        Some(length) => remaining.get(..length).ok_or_else(|| refuse("the body is shorter than the Content-Length"))?,
+        None => &[],
+    };
+
+    trace!("Request {} {} has been successfully parsed - Protocol Version HTTP/1.{}", method.to_string(), url, subversion);
+    Ok(Request {method, url, path, headers, cookies, param: params, query, body: body.to_vec(), extensions: Extensions::new(), states: Arc::new(Extensions::new())})
+}
+
+// Will decompress a body sent with a Content-Encoding: the Route reads it as it was before being encoded.
+// It is done once the Route is found, so that a Request no Route accepts is not decompressed.
+pub fn decode_body(request: &mut Request, limit: usize) -> Result<(), StatusCode> {
+    let content_encoding: Vec<String> = request.headers.get_list("Content-Encoding").iter().map(|c| c.to_string()).collect();
+    if content_encoding.is_empty() {
+        return Ok(());
+    }
+    let codings: Vec<&str> = content_encoding.iter().map(String::as_str).collect();
+    let decoded = decompress(&codings, std::mem::take(&mut request.body), limit)?;
+    request.headers.remove("Content-Encoding");
+    request.headers.insert("Content-Length", &decoded.len().to_string());
+    request.body = decoded;
+    Ok(())
+}
+
+// Content-Length: 2, 2 - A list of identical values is accepted as one of them. (RFC 9110 - 8.6)
+fn content_length_value(value: &str) -> Result<usize, &'static str> {
+    let mut length = None;
+    for member in value.split(',').map(str::trim) {
+        if member.is_empty() || !member.bytes().all(|b| b.is_ascii_digit()) {
+            return Err("the Content-Length is not a number");
+        }
+        let member = member.parse::<usize>().map_err(|_| "the Content-Length is out of range")?;
+        if length.is_some_and(|l| l != member) {
+            return Err("multiple Content-Length with different values");
+        }
+        length = Some(member);
+    }
+    length.ok_or("the Content-Length is not a number")
+}
+
+fn refuse(reason: &str) -> StatusCode {
+    trace!("Request is malformed: {} - Returning 400 Bad Request", reason);
+    StatusCode::BadRequest
+}
+
+// token = 1*tchar (RFC 9110 - 5.6.2)
+fn is_token(s: &str) -> bool {
+    !s.is_empty() && s.bytes().all(|b| b.is_ascii_alphanumeric() || b"!#$%&'*+-.^_`|~".contains(&b))
+}
+
+
+#[cfg(test)]
+mod test {
+
+    use super::*;
+
+    #[test]
+    fn valid_request() {
+        let request = parse_http1x(b"GET /Hello?name=Bernard&city=Saint-%C3%89tienne HTTP/1.1\r\nHost: localhost\r\nX-Custom:  a:b \r\nCookie: yummy=choco; tasty=strawberry\r\n\r\n", &1).unwrap();
+        assert_eq!(HttpMethod::GET, request.method);
+        assert_eq!("/hello", request.url);
+        assert_eq!(Some(&"Bernard".to_string()), request.param.get("name"));
+        assert_eq!(Some(&"Saint-Étienne".to_string()), request.param.get("city"));
+        assert_eq!(Some("a:b"), request.headers.get("x-custom"));
+        assert_eq!(Some(&"strawberry".to_string()), request.cookies.get("tasty"));
+    }
+
+    #[test]
+    fn body_is_bounded_by_content_length() {
+        let request = parse_http1x(b"POST /hello HTTP/1.1\r\nHost: localhost\r\nContent-Length: 5\r\n\r\nHello, World!", &1).unwrap();
+        assert_eq!(b"Hello".to_vec(), request.body);
+    }
+
+    #[test]
+    fn body_is_binary_safe() {
+        let raw = [b"POST /upload HTTP/1.1\r\nHost: localhost\r\nContent-Length: 4\r\n\r\n".as_ref(), &[0xff, 0x00, 0xd8, 0x80]].concat();
+        let request = parse_http1x(&raw, &1).unwrap();
+        assert_eq!(vec![0xff, 0x00, 0xd8, 0x80], request.body);
+        assert_eq!(Err(StatusCode::BadRequest), request.text());
+    }
+
+    #[test]
+    fn encoded_body() {
+        let mut request = parse_http1x(b"POST /upload HTTP/1.1\r\nHost: localhost\r\nContent-Encoding: identity\r\nContent-Length: 5\r\n\r\nHello", &1).unwrap();
+        assert_eq!(Ok(()), decode_body(&mut request, MAX_DECODED_BODY_SIZE));
+        assert_eq!(b"Hello".to_vec(), request.body);
+        let mut request = parse_http1x(b"POST /upload HTTP/1.1\r\nHost: localhost\r\nContent-Encoding: compress\r\nContent-Length: 5\r\n\r\nHello", &1).unwrap();
+        assert_eq!(b"Hello".to_vec(), request.body);
+        assert_eq!(Err(StatusCode::UnsupportedMediaType), decode_body(&mut request, MAX_DECODED_BODY_SIZE));
+    }
+
+    #[cfg(feature = "gzip")]
+    #[test]
+    fn gzip_body_is_decompressed() {
+        let gzip = crate::core::compression::Encoding::Gzip.encode(b"{\"name\": \"arkos\"}").unwrap();
+        let head = format!("POST /upload HTTP/1.1\r\nHost: localhost\r\nContent-Encoding: gzip\r\nContent-Length: {}\r\n\r\n", gzip.len());
+        let raw = [head.as_bytes(), &gzip].concat();
+        let mut request = parse_http1x(&raw, &1).unwrap();
+        assert_eq!(Err(StatusCode::PayloadTooLarge), decode_body(&mut request, 16));
+        let mut request = parse_http1x(&raw, &1).unwrap();
+        assert_eq!(Ok(()), decode_body(&mut request, 17));
+        assert_eq!(Ok("{\"name\": \"arkos\"}"), request.text());
+        assert_eq!(None, request.headers.get("Content-Encoding"));
+        assert_eq!(Some(17), request.headers.content_length());
     }
-    
-    let body_start = (pos + 4).min(raw_request.len());
-    let body = raw_request[body_start..].to_string();
-    trace!("Request {} {} has been successfully parsed - Protocol Version HTTP/1.1", method.to_string(), url);
-    Ok(Request {method, url: url.to_string(), headers, cookies, param: params, body})
-}
\ No newline at end of file
+
+    #[test]
+    fn bare_lf_is_refused() {
+        assert_eq!(Err(StatusCode::BadRequest), parse_http1x(b"GET /hello HTTP/1.1\nHost: localhost\r\n\r\n", &1).map(|_| ()));
+    }
+
+    #[test]
+    fn malformed_request_line_is_refused() {
+        assert_eq!(Err(StatusCode::BadRequest), parse_http1x(b"GET  /hello HTTP/1.1\r\nHost: localhost\r\n\r\n", &1).map(|_| ()));
+        assert_eq!(Err(StatusCode::BadRequest), parse_http1x(b"GET /hello HTTP/1.1 \r\nHost: localhost\r\n\r\n", &1).map(|_| ()));
+        assert_eq!(Err(StatusCode::BadRequest), parse_http1x(b"GET hello HTTP/1.1\r\nHost: localhost\r\n\r\n", &1).map(|_| ()));
+    }
+
+    #[test]
+    fn unknown_method_is_not_implemented() {
+        assert_eq!(Err(StatusCode::NotImplemented), parse_http1x(b"BREW /pot HTTP/1.1\r\nHost: localhost\r\n\r\n", &1).map(|_| ()));
+    }
+
+    #[test]
+    fn obs_fold_is_refused() {
+        assert_eq!(Err(StatusCode::BadRequest), parse_http1x(b"GET /hello HTTP/1.1\r\nHost: localhost\r\nX-Custom: a\r\n b\r\n\r\n", &1).map(|_| ()));
+    }
+
+    #[test]
+    fn whitespace_before_colon_is_refused() {
+        assert_eq!(Err(StatusCode::BadRequest), parse_http1x(b"GET /hello HTTP/1.1\r\nHost : localhost\r\n\r\n", &1).map(|_| ()));
+    }
+
+    #[test]
+    fn missing_host_is_refused() {
+        assert_eq!(Err(StatusCode::BadRequest), parse_http1x(b"GET /hello HTTP/1.1\r\n\r\n", &1).map(|_| ()));
+        assert!(parse_http1x(b"GET /hello HTTP/1.0\r\n\r\n", &0).is_ok());
+    }
+
+    #[test]
+    fn duplicate_content_length() {
+        assert!(parse_http1x(b"POST /hello HTTP/1.1\r\nHost: localhost\r\nContent-Length: 2\r\nContent-Length: 2\r\n\r\nHi", &1).is_ok());
+        assert_eq!(Err(StatusCode::BadRequest), parse_http1x(b"POST /hello HTTP/1.1\r\nHost: localhost\r\nContent-Length: 2\r\nContent-Length: 3\r\n\r\nHi!", &1).map(|_| ()));
+        assert_eq!(Err(StatusCode::BadRequest), parse_http1x(b"POST /hello HTTP/1.1\r\nHost: localhost\r\nContent-Length: -2\r\n\r\nHi", &1).map(|_| ()));
+    }
+
+    #[test]
+    fn uri_too_long() {
+        let request = format!("GET /{} HTTP/1.1\r\nHost: localhost\r\n\r\n", "a".repeat(MAX_URI_LENGTH));
+        assert_eq!(Err(StatusCode::URIToolLong), parse_http1x(request.as_bytes(), &1).map(|_| ()));
+    }
+
+    #[test]
+    fn header_fields_too_large() {
+        let request = format!("GET /hello HTTP/1.1\r\nHost: localhost\r\nX-Custom: {}\r\n\r\n", "a".repeat(MAX_HEADER_SECTION_SIZE));
+        assert_eq!(Err(StatusCode::RequestHeaderFieldsTooLarge), parse_http1x(request.as_bytes(), &1).map(|_| ()));
+    }
+
+    #[test]
+    fn request_line_is_found() {
+        assert_eq!("GET /hello HTTP/1.1", request_line(b"\r\nGET /hello HTTP/1.1\r\nHost: localhost\r\n\r\n"));
+    }
+
+    #[test]
+    fn read_stops_at_content_length() {
+        let mut stream: &[u8] = b"POST /hello HTTP/1.1\r\nHost: localhost\r\nContent-Length: 2\r\n\r\nHi, and more";
+        let raw = read_http1x(&mut stream).unwrap().unwrap();
+        assert!(raw.ends_with(b"\r\n\r\nHi"));
+    }
+
+    #[test]
+    fn read_list_content_length() {
+        let mut stream: &[u8] = b"POST /hello HTTP/1.1\r\nHost: localhost\r\nContent-Length: 2, 2\r\n\r\nHi, and more";
+        let raw = read_http1x(&mut stream).unwrap().unwrap();
+        assert!(raw.ends_with(b"\r\n\r\nHi"));
+        assert_eq!(b"Hi".to_vec(), parse_http1x(&raw, &1).unwrap().body);
+    }
+
+}
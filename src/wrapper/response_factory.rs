@@ -1,9 +1,30 @@
-use std::collections::HashMap;
+use crate::{server::{protocol::Protocol, response::Response}, core::{status::StatusCode, status::HttpStatusCode, content::ContentType, method::{HttpMethod}, header::HeaderMap, body::BodyFile, date::HttpDate}};
 
-use crate::{server::{protocol::Protocol, response::Response}, core::{status::StatusCode, status::HttpStatusCode, content::ContentType, method::{HttpMethod}}};
+use std::io::{self, Write};
+use std::net::TcpStream;
+
+use log::warn;
 
 use super::cookie_factory::generate_header;
 
+// Size of the chunks read from a streamed body.
+const CHUNK_SIZE: usize = 8192;
+
+// Where a Response is written. A TCP connection sends a file body with `sendfile` on Linux.
+pub trait Connection: Write {
+    fn send_file(&mut self, file: &BodyFile) -> io::Result<()> {
+        file.copy_to(self)
+    }
+}
+
+impl Connection for TcpStream {
+    fn send_file(&mut self, file: &BodyFile) -> io::Result<()> {
+        file.send_to(self)
+    }
+}
+
+impl Connection for Vec<u8> {}
+
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct ResponseFactory {
     protocol: Protocol,
@@ -17,7 +38,7 @@ impl ResponseFactory {
     
     // StatusCode
     pub fn for_status_code(protocol: Protocol, code: StatusCode) -> Self {
-        let response = Response {status: code, content_type: ContentType::Text, headers: HashMap::new(), cookies: Vec::new(), body: "".into()};
+        let response = Response {status: code, content_type: ContentType::Text, headers: HeaderMap::new(), cookies: Vec::new(), body: Vec::new(), stream: None, file: None};
         ResponseFactory {protocol, method: HttpMethod::GET, response, following_response: Vec::new()}
     }
 
@@ -32,14 +53,67 @@ impl ResponseFactory {
     // }
 
     // Consume
-    pub fn consume(&mut self) -> String {
+    pub fn consume(&mut self) -> Vec<u8> {
         match &self.protocol {
             Protocol::Http1(v) => convert_http1(&mut self.response, Protocol::Http1(*v), self.method),
             _ => convert_http1(&mut self.response, Protocol::Http1(0), self.method)
         }
     }
     
-    // Convert HTTP1.x
+    // Write the Response, sending a file or reading a streamed body while it is sent.
+    pub fn write_to<W: Connection>(&mut self, writer: &mut W) -> io::Result<()> {
+        let version = match &self.protocol {
+            Protocol::Http1(v) => *v,
+            _ => 0,
+        };
+        if self.response.status == StatusCode::NotModified {
+            self.response.stream = None;
+            self.response.file = None;
+        }
+        if let Some(file) = self.response.file.take() {
+            self.response.headers.insert("Content-Length", &file.len().to_string());
+            writer.write_all(&head_http1(&mut self.response, Protocol::Http1(version), false))?;
+            if self.method != HttpMethod::HEAD {
+                writer.send_file(&file)?;
+            }
+            return writer.flush();
+        }
+        let stream = match self.response.stream.take() {
+            Some(stream) => stream,
+            None => return writer.write_all(&self.consume()),
+        };
+        // HTTP/1.0 doesn't know chunks: the body ends with the connection. (RFC 9112 - 6.3)
+        let chunked = version >= 1;
+        match chunked {
+            true => self.response.headers.insert("Transfer-Encoding", "chunked"),
+            false => self.response.headers.insert("Connection", "close"),
+        }
+        writer.write_all(&head_http1(&mut self.response, Protocol::Http1(version), false))?;
+        if self.method == HttpMethod::HEAD {
+            return Ok(());
+        }
+
+        let mut buf = vec![0u8; CHUNK_SIZE];
+        loop {
+            let n = stream.read(&mut buf)?;
+            if n == 0 {
+                break;
+            }
+            match chunked {
+                true => {
+                    writer.write_all(format!("{:x}\r\n", n).as_bytes())?;
+                    writer.write_all(&buf[..n])?;
+                    writer.write_all(b"\r\n")?;
+                }
+                false => writer.write_all(&buf[..n])?,
+            }
+        }
+        if chunked {
+            writer.write_all(b"0\r\n\r\n")?;
+        }
+        writer.flush()
+    }
+
 
 
 
@@ -60,14 +134,39 @@ impl ResponseFactory {
 // }
 
 // Convert a response to a String to be sent back - Needs HTTP Protocol.
-fn convert_http1(response: &mut Response, protocol: Protocol, method: HttpMethod) -> String {
+fn convert_http1(response: &mut Response, protocol: Protocol, method: HttpMethod) -> Vec<u8> {
+    let mut s = head_http1(response, protocol, true);
+    // Add the body only if the Method is not HEAD
+    if !method.eq(&HttpMethod::HEAD) {
+        s.extend_from_slice(&response.body);
+    }
+
+    s
+}
+
+// Status line and headers - Content-Length is only known when the body is not streamed.
+fn head_http1(response: &mut Response, protocol: Protocol, content_length: bool) -> Vec<u8> {
     let mut headers = String::new();
-    response.headers.insert("Content-Length".to_string(), response.body.len().to_string());
-    response.headers.insert("Content-Type".to_string(), response.content_type.get());
+    // A Custom status built by hand may not fit the status line.
+    if !(100..=999).contains(&response.status.get_code()) {
+        warn!("{} is not a status code - Returning 500 Internal Server Error", response.status.get_code());
+        response.status = StatusCode::InternalServerError;
+    }
+    // A 304 Not Modified has no content. (RFC 9110 - 15.4.5)
+    if response.status != StatusCode::NotModified {
+        if content_length {
+            response.headers.insert("Content-Length", &response.body.len().to_string());
+        }
+        response.headers.insert("Content-Type", &response.content_type.get());
+    }
+    // An origin server with a clock sends the date of the Response. (RFC 9110 - 6.6.1)
+    if !response.headers.contains_key("Date") {
+        response.headers.insert("Date", &HttpDate::now_header());
+    }
     
     headers.push_str(&response.status.generate_headers());
     for (key, val) in response.headers.iter() {
-        let entry: String = format!("{}:{}\r\n", key, val);
+        let entry: String = format!("{}: {}\r\n", key, val);
         headers.push_str(&entry);
     }
     
@@ -76,16 +175,118 @@ fn convert_http1(response: &mut Response, protocol: Protocol, method: HttpMethod
         headers.push_str(&entry)
     }
 
-    // 1. Add the First line and the Headers.
-    let mut s = format!("HTTP/1.{} {} {}\r\n{}", 
+    format!("HTTP/1.{} {} {}\r\n{}\r\n", 
     protocol.get_version(),
     response.status.get_code(), response.status.get_title(),
     headers, 
-    );
-    // 2. Add the body only if the Method is not HEAD
-    if !method.eq(&HttpMethod::HEAD) {
-        s.push_str(&format!("\r\n{}", response.body));
+    ).into_bytes()
+}
+
+
+#[cfg(test)]
+mod test {
+
+    use super::*;
+
+    // The Date header changes every second.
+    fn without_date(raw: Vec<u8>) -> String {
+        let raw = String::from_utf8(raw).unwrap();
+        let start = raw.find("Date: ").unwrap();
+        let end = start + raw[start..].find("\r\n").unwrap() + 2;
+        format!("{}{}", &raw[..start], &raw[end..])
+    }
+
+    #[test]
+    fn date_is_added() {
+        let raw = String::from_utf8(ResponseFactory::new(Protocol::Http1(1), HttpMethod::GET, Response::default()).consume()).unwrap();
+        let date = raw.lines().find_map(|l| l.strip_prefix("Date: ")).unwrap();
+        assert!(HttpDate::parse(date).is_some());
+
+        let mut response = Response::default();
+        response.set_header("Date", "Sun, 06 Nov 1994 08:49:37 GMT");
+        let raw = String::from_utf8(ResponseFactory::new(Protocol::Http1(1), HttpMethod::GET, response).consume()).unwrap();
+        assert_eq!(1, raw.matches("Date: ").count());
+        assert!(raw.contains("Date: Sun, 06 Nov 1994 08:49:37 GMT\r\n"));
+    }
+
+    #[test]
+    fn headers_are_serialized_in_insertion_order() {
+        let mut response = Response::default();
+        response.add_header("X-First".into(), "1".into());
+        response.add_header("Set-Token".into(), "a".into());
+        response.add_header("Set-Token".into(), "b".into());
+        let raw = String::from_utf8(ResponseFactory::new(Protocol::Http1(1), HttpMethod::GET, response).consume()).unwrap();
+        assert!(raw.contains("X-First: 1\r\nSet-Token: a\r\nSet-Token: b\r\nContent-Length: 0\r\n"));
+    }
+
+    #[test]
+    fn unauthorized_challenge_is_serialized() {
+        let raw = String::from_utf8(ResponseFactory::for_status_code(Protocol::Http1(1), StatusCode::Unauthorized("Basic realm=\"Arkos\"".into())).consume()).unwrap();
+        assert!(raw.starts_with("HTTP/1.1 401 Unauthorized\r\nWWW-Authenticate: Basic realm=\"Arkos\"\r\n"));
+        let raw = String::from_utf8(ResponseFactory::for_status_code(Protocol::Http1(1), StatusCode::Unauthorized("".into())).consume()).unwrap();
+        assert!(!raw.contains("WWW-Authenticate"));
+    }
+
+    #[test]
+    fn invalid_custom_status_is_refused() {
+        let mut response = Response::default();
+        response.status = StatusCode::Custom(42, "Answer".into());
+        let raw = String::from_utf8(ResponseFactory::new(Protocol::Http1(1), HttpMethod::GET, response).consume()).unwrap();
+        assert!(raw.starts_with("HTTP/1.1 500 Internal Server Error\r\n"));
+    }
+
+    #[test]
+    fn not_modified_has_no_content() {
+        let mut response = Response::default();
+        response.status = StatusCode::NotModified;
+        response.set_header("ETag", "\"v1\"");
+        let raw = without_date(ResponseFactory::new(Protocol::Http1(1), HttpMethod::GET, response).consume());
+        assert_eq!("HTTP/1.1 304 Not Modified\r\nETag: \"v1\"\r\n\r\n", raw);
+    }
+
+    #[test]
+    fn stream_is_chunked_on_http11() {
+        let mut response = Response::default();
+        response.set_content_type(ContentType::Text);
+        response.set_body_stream(std::io::Cursor::new(vec![b'a'; CHUNK_SIZE + 3]));
+        let mut raw = Vec::new();
+        ResponseFactory::new(Protocol::Http1(1), HttpMethod::GET, response).write_to(&mut raw).unwrap();
+        let raw = without_date(raw);
+        assert!(raw.starts_with("HTTP/1.1 200 Ok\r\nTransfer-Encoding: chunked\r\nContent-Type: text/plain\r\n\r\n2000\r\naaa"));
+        assert!(raw.ends_with("aaa\r\n3\r\naaa\r\n0\r\n\r\n"));
+        assert!(!raw.contains("Content-Length"));
+    }
+
+    #[test]
+    fn file_has_its_length() {
+        let path = std::env::temp_dir().join(format!("arkos-factory-{}", std::process::id()));
+        std::fs::write(&path, "id,name\n1,arkos\n").unwrap();
+        let mut response = Response::default();
+        response.set_content_type(ContentType::Text);
+        response.set_body_file(BodyFile::open(&path).unwrap());
+        std::fs::remove_file(&path).unwrap();
+
+        let mut raw = Vec::new();
+        ResponseFactory::new(Protocol::Http1(1), HttpMethod::GET, response.clone()).write_to(&mut raw).unwrap();
+        assert_eq!("HTTP/1.1 200 Ok\r\nContent-Length: 16\r\nContent-Type: text/plain\r\n\r\nid,name\n1,arkos\n", without_date(raw));
+
+        let mut raw = Vec::new();
+        ResponseFactory::new(Protocol::Http1(1), HttpMethod::HEAD, response).write_to(&mut raw).unwrap();
+        assert_eq!("HTTP/1.1 200 Ok\r\nContent-Length: 16\r\nContent-Type: text/plain\r\n\r\n", without_date(raw));
+    }
+
+    #[test]
+    fn stream_ends_with_connection_on_http10() {
+        let mut response = Response::default();
+        response.set_content_type(ContentType::Text);
+        response.set_body_stream("id,name\n1,arkos\n".as_bytes());
+        let mut raw = Vec::new();
+        ResponseFactory::new(Protocol::Http1(0), HttpMethod::GET, response.clone()).write_to(&mut raw).unwrap();
+        assert_eq!("HTTP/1.0 200 Ok\r\nConnection: close\r\nContent-Type: text/plain\r\n\r\nid,name\n1,arkos\n", without_date(raw));
+
+        let mut raw = Vec::new();
+        ResponseFactory::new(Protocol::Http1(1), HttpMethod::HEAD, response).write_to(&mut raw).unwrap();
+        assert_eq!("HTTP/1.1 200 Ok\r\nTransfer-Encoding: chunked\r\nContent-Type: text/plain\r\n\r\n", without_date(raw));
     }
 
-    s
 }